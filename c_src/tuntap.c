@@ -11,6 +11,9 @@
 extern "C" {
 #endif
 int32_t setup_tun_device(int32_t fd, char const *ifname);
+int32_t setup_tap_device(int32_t fd, char const *ifname);
+int32_t setup_tun_mq_device(int32_t fd, char const *ifname);
+int32_t setup_tap_mq_device(int32_t fd, char const *ifname);
 
 #ifdef __cplusplus
 } // extern "C"
@@ -32,3 +35,16 @@ int32_t setup_device(int32_t fd, char const *ifname, int32_t flags) {
 int32_t setup_tun_device(int32_t fd, char const *ifname) {
   return setup_device(fd, ifname, IFF_TUN | IFF_NO_PI);
 }
+
+int32_t setup_tap_device(int32_t fd, char const *ifname) {
+  return setup_device(fd, ifname, IFF_TAP | IFF_NO_PI);
+}
+
+// every call open one more queue of the same device
+int32_t setup_tun_mq_device(int32_t fd, char const *ifname) {
+  return setup_device(fd, ifname, IFF_TUN | IFF_NO_PI | IFF_MULTI_QUEUE);
+}
+
+int32_t setup_tap_mq_device(int32_t fd, char const *ifname) {
+  return setup_device(fd, ifname, IFF_TAP | IFF_NO_PI | IFF_MULTI_QUEUE);
+}
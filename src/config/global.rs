@@ -24,6 +24,14 @@ lazy_static! {
                     .default_value("./config.yaml")
                     .help("config file location"),
             )
+            .subcommand(
+                SubCommand::with_name("genkey")
+                    .about("Generate a private key and write it to stdout"),
+            )
+            .subcommand(
+                SubCommand::with_name("pubkey")
+                    .about("Read a private key from stdin and write the public key to stdout"),
+            )
             .get_matches()
     };
     pub static ref CONFIG: Config = crate::config::Config::from_path(ARG.value_of("file").unwrap());
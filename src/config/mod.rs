@@ -1,12 +1,13 @@
 use crate::interface::Type;
-use clap::{App, Arg, ArgMatches};
+use crate::utils;
+use clap::{App, Arg, ArgMatches, SubCommand};
 use log::*;
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
 use serde::{self, Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::path::Path;
 
 pub mod global;
@@ -20,7 +21,6 @@ pub struct Config {
     pub device_name: String,
 
     /// device type, only `tun` or `tap`
-    /// but tap is not implemented
     pub device_type: Type,
 
     /// listen port, both TCP and UDP will be listen
@@ -29,7 +29,18 @@ pub struct Config {
     /// self subnet like "127.0.0.0/24"
     pub subnet: String,
 
-    /// can use `$INTERFACE` `$IP_ADDR_MASK` as device name and ip address
+    /// self IPv6 subnets for dual stack, like "fd00:1::1/64",
+    /// the address is set on the device and the prefix is announced
+    #[serde(default)]
+    pub subnet_v6: Vec<String>,
+
+    /// more subnets announced by myself, like the LANs behind a gateway,
+    /// read again on SIGHUP, a removed one is withdrawn
+    #[serde(default)]
+    pub advertise: Vec<String>,
+
+    /// can use `$INTERFACE` `$IP_ADDR_MASK` as device name and ip address,
+    /// `$IP6_ADDR_MASK` is the IPv6 subnets split by space
     /// see `config.example.yaml`
     pub ifup: String,
 
@@ -41,6 +52,115 @@ pub struct Config {
 
     /// myself name
     pub name: String,
+
+    /// pre-shared network secret, every datagram is encrypted by a key
+    /// derived from it, all node in the same network must use the same one
+    pub secret: String,
+
+    /// file of the base64 X25519 private key of this node,
+    /// create by `placeholder genkey > private.key`,
+    /// a new key is generated and saved if the file is not exist
+    #[serde(default = "default_private_key_file")]
+    pub private_key_file: String,
+
+    /// the allow-list, only node with these public key can join the network,
+    /// and can only announce the subnets listed here
+    #[serde(default)]
+    pub peers: Vec<Peer>,
+
+    /// seconds between two keepalive
+    #[serde(default = "default_keepalive_interval")]
+    pub keepalive_interval: u64,
+
+    /// missed keepalive to mark a address unreachable,
+    /// the address is removed after 4 times of it
+    #[serde(default = "default_keepalive_misses")]
+    pub keepalive_misses: u32,
+
+    /// seconds between two full route update to every neighbor,
+    /// a route not updated in 4 times of it is retracted
+    #[serde(default = "default_update_interval")]
+    pub update_interval: u64,
+
+    /// routing protocol, `distance_vector` or `link_state`,
+    /// all node in the same network must use the same one
+    #[serde(default = "default_routing")]
+    pub routing: Routing,
+
+    /// act as a internet gateway, announce `0.0.0.0/0` and `::/0`
+    #[serde(default)]
+    pub exit_node: bool,
+
+    /// run after `ifup` on a exit node, like enabling forwarding and
+    /// masquerade, same environment as `ifup`
+    #[serde(default)]
+    pub exit_up: String,
+
+    /// same as `exit_up`, run before `ifdown`
+    #[serde(default)]
+    pub exit_down: String,
+
+    /// exit nodes to use in order of preference, the next one is used when
+    /// the former is down, any exit node is used if empty
+    #[serde(default)]
+    pub exit_nodes: Vec<String>,
+
+    /// firewall mark of the UDP sockets, so `ip rule add not fwmark` can
+    /// keep the overlay traffic out of the tunnel, 0 to not set it
+    #[serde(default)]
+    pub fwmark: u32,
+
+    /// forward package between other node which can not reach each other,
+    /// a node not a relay only announce its own subnets
+    #[serde(default = "default_relay")]
+    pub relay: bool,
+
+    /// KiB per second forwarded for other node as a relay, 0 is unlimited
+    #[serde(default)]
+    pub relay_rate: u64,
+
+    /// queues of the device, every one is polled by a worker thread with its
+    /// own socket and router, 0 is one for every CPU
+    #[serde(default = "default_queues")]
+    pub queues: usize,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Routing {
+    /// announce subnets to neighbors with sequence numbers, see `router::dv`
+    #[serde(rename = "distance_vector")]
+    DistanceVector,
+    /// flood the links of every node and run dijkstra, see `router::ls`
+    #[serde(rename = "link_state")]
+    LinkState,
+}
+
+fn default_private_key_file() -> String {
+    "./private.key".to_string()
+}
+
+fn default_keepalive_interval() -> u64 {
+    10
+}
+
+fn default_keepalive_misses() -> u32 {
+    3
+}
+
+fn default_update_interval() -> u64 {
+    30
+}
+
+fn default_routing() -> Routing {
+    Routing::DistanceVector
+}
+
+fn default_relay() -> bool {
+    true
+}
+
+fn default_queues() -> usize {
+    1
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -51,6 +171,15 @@ pub struct Server {
     pub name: String,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Peer {
+    pub name: String,
+    /// base64 X25519 public key
+    pub public_key: String,
+    /// subnets this node can announce, like "10.1.0.0/16"
+    pub subnets: Vec<String>,
+}
+
 impl Config {
     pub fn get() -> &'static Self {
         &CONFIG
@@ -63,20 +192,73 @@ impl Config {
         P: AsRef<Path> + Send + 'static,
     {
         let data = fs::read(path);
-        let config = serde_yaml::from_slice(&data.unwrap()).unwrap_or_else(|e| {
-            log::warn!("decode error, {}, fall back to default config", e);
-            Self::default()
+        // a default config has a random name and secret, never run with it
+        // in place of a broken file
+        let config: Self = serde_yaml::from_slice(&data.unwrap()).unwrap_or_else(|e| {
+            eprintln!("can not decode the config file, {}", e);
+            std::process::exit(1);
         });
+        if let Err(e) = config.check() {
+            eprintln!("bad config file, {}", e);
+            std::process::exit(1);
+        }
         info!("loaded config: {:?}", config);
         config
     }
 
+    /// read the config file again, without the fall back
+    pub fn reload() -> Result<Self, String> {
+        let path = ARG.value_of("file").unwrap();
+        let data = fs::read(path).map_err(|e| e.to_string())?;
+        let config: Self = serde_yaml::from_slice(&data).map_err(|e| e.to_string())?;
+        config.check()?;
+        Ok(config)
+    }
+
     pub fn get_env(&self) -> HashMap<String, String> {
-        let mut h = HashMap::with_capacity(2);
+        let mut h = HashMap::with_capacity(3);
         h.insert("INTERFACE".to_string(), self.device_name.clone());
         h.insert("IP_ADDR_MASK".to_string(), self.subnet.clone());
+        h.insert("IP6_ADDR_MASK".to_string(), self.subnet_v6.join(" "));
+        h.insert("FWMARK".to_string(), self.fwmark.to_string());
         h
     }
+
+    /// number of worker threads, same as the queues of the device
+    pub fn workers(&self) -> usize {
+        if self.queues > 0 {
+            return self.queues;
+        }
+        let cpus = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+        cpus.max(1) as usize
+    }
+}
+
+impl Config {
+    /// only the node `name` in the allow-list can announce `addr/mask`,
+    /// and it must be inside one of the subnets of the node
+    pub fn allow_announce(&self, name: &str, addr: IpAddr, mask: u32) -> bool {
+        self.peers
+            .iter()
+            .filter(|p| p.name == name)
+            .flat_map(|p| p.subnets.iter())
+            .filter_map(|s| utils::parse_cidr(s))
+            .any(|(net, net_mask)| net_mask <= mask && utils::cidr_contains(net, net_mask, addr))
+    }
+
+    /// a default route is only accepted from a node in `exit_nodes`,
+    /// or from any node if `exit_nodes` is empty
+    pub fn accept_exit(&self, name: &str, mask: u32) -> bool {
+        mask != 0 || self.exit_nodes.is_empty() || self.exit_nodes.iter().any(|n| n == name)
+    }
+
+    /// position of `name` in `exit_nodes`, smaller is preferred
+    pub fn exit_rank(&self, name: &str) -> usize {
+        self.exit_nodes
+            .iter()
+            .position(|n| n == name)
+            .unwrap_or(self.exit_nodes.len())
+    }
 }
 
 impl Config {
@@ -91,6 +273,79 @@ impl Config {
         let t = t[1];
         t.parse().unwrap()
     }
+
+    /// address and mask of every `subnet_v6`
+    pub fn get_v6(&self) -> Vec<(Ipv6Addr, u32)> {
+        self.subnet_v6
+            .iter()
+            .filter_map(|s| match utils::parse_cidr(s) {
+                Some((IpAddr::V6(addr), mask)) => Some((addr, mask)),
+                _ => {
+                    warn!("{} is not a IPv6 subnet, ignore it", s);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// every subnet in `advertise`
+    pub fn get_advertise(&self) -> Vec<(IpAddr, u16)> {
+        self.advertise
+            .iter()
+            .filter_map(|s| match utils::parse_cidr(s) {
+                Some((addr, mask)) => Some((addr, mask as u16)),
+                None => {
+                    warn!("{} is not a subnet, ignore it", s);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// every subnet announced by myself, `get_subnets` and `get_advertise`,
+    /// and the default routes of a exit node, every one only once
+    pub fn get_announce(&self) -> Vec<(IpAddr, u16)> {
+        let mut v = self.get_subnets();
+        v.extend(self.get_advertise());
+        if self.exit_node {
+            v.push((Ipv4Addr::UNSPECIFIED.into(), 0));
+            v.push((Ipv6Addr::UNSPECIFIED.into(), 0));
+        }
+        let mut seen = Vec::with_capacity(v.len());
+        v.retain(|p| {
+            if seen.contains(p) {
+                return false;
+            }
+            seen.push(*p);
+            true
+        });
+        v
+    }
+
+    /// a subnet listed twice in `subnet`, `subnet_v6` and `advertise` is a
+    /// mistake in the config, the default routes of a exit node can be
+    /// listed again
+    pub fn check(&self) -> Result<(), String> {
+        let mut v = self.get_subnets();
+        v.extend(self.get_advertise());
+        for (i, p) in v.iter().enumerate() {
+            if v[..i].contains(p) {
+                return Err(format!("{}/{} is listed twice", p.0, p.1));
+            }
+        }
+        Ok(())
+    }
+
+    /// every subnet of the device, v4 and v6
+    pub fn get_subnets(&self) -> Vec<(IpAddr, u16)> {
+        let mut v = vec![(self.get_v4().into(), self.get_v4_mask() as u16)];
+        v.extend(
+            self.get_v6()
+                .into_iter()
+                .map(|(addr, mask)| (addr.into(), mask as u16)),
+        );
+        v
+    }
 }
 
 impl Default for Config {
@@ -100,6 +355,8 @@ impl Default for Config {
             device_type: Type::Tun,
             port: 7654,
             subnet: "128.66.1.0/32".to_string(),
+            subnet_v6: vec![],
+            advertise: vec![],
             ifup: "/bin/sh -c 'exit 1'".to_string(),
             ifdown: "/bin/sh -c 'exit 1'".to_string(),
             servers: vec![],
@@ -109,6 +366,24 @@ impl Default for Config {
                     .take(5)
                     .collect::<String>()
                     .as_str(),
+            secret: thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(32)
+                .collect::<String>(),
+            private_key_file: default_private_key_file(),
+            peers: vec![],
+            keepalive_interval: default_keepalive_interval(),
+            keepalive_misses: default_keepalive_misses(),
+            update_interval: default_update_interval(),
+            routing: default_routing(),
+            exit_node: false,
+            exit_up: String::new(),
+            exit_down: String::new(),
+            exit_nodes: vec![],
+            fwmark: 0,
+            relay: default_relay(),
+            relay_rate: 0,
+            queues: default_queues(),
         };
         info!("loaded default config: {:?}", c);
         c
@@ -0,0 +1,235 @@
+use super::{hmac, Error, Key, KEY_LEN, TAG_LEN};
+use crate::generated::transport::{Handshake, Handshake_Step};
+use log::*;
+use openssl::base64;
+use openssl::derive::Deriver;
+use openssl::error::ErrorStack;
+use openssl::pkey::{Id, PKey, Private};
+use openssl::sha::Sha256;
+use openssl::symm::{self, Cipher};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::net::SocketAddr;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::time::Instant;
+
+const CONSTRUCTION: &[u8] = b"placeholder handshake v1 X25519 ChaCha20Poly1305 SHA256";
+
+/// the X25519 static key of a node
+pub struct Identity {
+    key: PKey<Private>,
+    public: Key,
+}
+
+impl Identity {
+    pub fn generate() -> Self {
+        Self::from_key(PKey::generate_x25519().expect("generate x25519 key"))
+            .expect("read x25519 public key")
+    }
+
+    /// read a base64 encoded raw X25519 private key
+    pub fn from_base64(s: &str) -> Result<Self, Error> {
+        let raw = base64::decode_block(s.trim()).map_err(Error::BadKey)?;
+        let key = PKey::private_key_from_raw_bytes(&raw, Id::X25519).map_err(Error::BadKey)?;
+        Self::from_key(key).map_err(Error::BadKey)
+    }
+
+    /// read the private key file, generate and save a new one if not exist
+    pub fn load_or_generate<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(s) => Self::from_base64(&s)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+                let identity = Self::generate();
+                warn!("{:?} not exist, generate a new key", path);
+                let mut f = OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .mode(0o600)
+                    .open(path)?;
+                writeln!(f, "{}", identity.private_base64())?;
+                Ok(identity)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn from_key(key: PKey<Private>) -> Result<Self, ErrorStack> {
+        let public = to_key(&key.raw_public_key()?).ok_or_else(ErrorStack::get)?;
+        Ok(Self { key, public })
+    }
+
+    pub fn public_key(&self) -> &Key {
+        &self.public
+    }
+
+    pub fn private_base64(&self) -> String {
+        base64::encode_block(&self.key.raw_private_key().expect("read x25519 private key"))
+    }
+
+    pub fn public_base64(&self) -> String {
+        base64::encode_block(&self.public)
+    }
+}
+
+/// decode a base64 encoded raw X25519 public key
+pub fn public_from_base64(s: &str) -> Result<Key, Error> {
+    let raw = base64::decode_block(s.trim()).map_err(Error::BadKey)?;
+    to_key(&raw).ok_or(Error::Handshake("public key must be 32 bytes"))
+}
+
+/// the initiator side state, wait for the response
+pub struct Initiation {
+    pub index: u32,
+    pub addr: SocketAddr,
+    pub sent: Instant,
+    ephemeral: PKey<Private>,
+}
+
+/// directional transport keys of a session
+pub struct Keys {
+    pub send: Key,
+    pub recv: Key,
+}
+
+/// start a handshake to `addr`, the static key of the responder is not
+/// needed, it is learned and checked from the response
+pub fn initiate(identity: &Identity, index: u32, addr: SocketAddr) -> (Initiation, Handshake) {
+    let ephemeral = PKey::generate_x25519().expect("generate x25519 key");
+
+    let mut m = Handshake::new();
+    m.set_step(Handshake_Step::INITIATION);
+    m.set_sender(index);
+    m.set_ephemeral(ephemeral.raw_public_key().expect("read x25519 public key"));
+    m.set_static_key(identity.public.to_vec());
+
+    let state = Initiation {
+        index,
+        addr,
+        sent: Instant::now(),
+        ephemeral,
+    };
+    (state, m)
+}
+
+/// answer a initiation, return the session keys and the response message
+///
+/// the initiator is not proven yet, it proves itself by the first transport
+/// package sealed with these keys
+pub fn respond(
+    identity: &Identity,
+    init: &Handshake,
+    index: u32,
+) -> Result<(Keys, Handshake), Error> {
+    if init.get_step() != Handshake_Step::INITIATION {
+        return Err(Error::Handshake("expect initiation"));
+    }
+    let e_i = to_key(init.get_ephemeral()).ok_or(Error::Handshake("bad ephemeral key"))?;
+    let s_i = to_key(init.get_static_key()).ok_or(Error::Handshake("bad static key"))?;
+
+    let ephemeral = PKey::generate_x25519().map_err(Error::BadKey)?;
+    let e_r = to_key(&ephemeral.raw_public_key().map_err(Error::BadKey)?)
+        .ok_or(Error::Handshake("bad ephemeral key"))?;
+
+    let dh1 = dh(&ephemeral, &e_i)?;
+    let dh2 = dh(&identity.key, &e_i)?;
+    let dh3 = dh(&ephemeral, &s_i)?;
+
+    let h = transcript(&e_i, &s_i, &e_r, &identity.public);
+    let (confirm, i2r, r2i) = derive(&h, &[&dh1, &dh2, &dh3]);
+
+    let mut m = Handshake::new();
+    m.set_step(Handshake_Step::RESPONSE);
+    m.set_sender(index);
+    m.set_receiver(init.get_sender());
+    m.set_ephemeral(e_r.to_vec());
+    m.set_static_key(identity.public.to_vec());
+    m.set_mac(mac(&confirm, &h)?.to_vec());
+
+    Ok((
+        Keys {
+            send: r2i,
+            recv: i2r,
+        },
+        m,
+    ))
+}
+
+/// finish the handshake on the initiator side, verify the responder own the
+/// static key in the response
+pub fn complete(identity: &Identity, state: &Initiation, resp: &Handshake) -> Result<Keys, Error> {
+    if resp.get_step() != Handshake_Step::RESPONSE {
+        return Err(Error::Handshake("expect response"));
+    }
+    let e_i = to_key(&state.ephemeral.raw_public_key().map_err(Error::BadKey)?)
+        .ok_or(Error::Handshake("bad ephemeral key"))?;
+    let e_r = to_key(resp.get_ephemeral()).ok_or(Error::Handshake("bad ephemeral key"))?;
+    let s_r = to_key(resp.get_static_key()).ok_or(Error::Handshake("bad static key"))?;
+
+    let dh1 = dh(&state.ephemeral, &e_r)?;
+    let dh2 = dh(&state.ephemeral, &s_r)?;
+    let dh3 = dh(&identity.key, &e_r)?;
+
+    let h = transcript(&e_i, &identity.public, &e_r, &s_r);
+    let (confirm, i2r, r2i) = derive(&h, &[&dh1, &dh2, &dh3]);
+
+    if mac(&confirm, &h)?[..] != resp.get_mac()[..] {
+        return Err(Error::Handshake("response mac mismatch"));
+    }
+
+    Ok(Keys {
+        send: i2r,
+        recv: r2i,
+    })
+}
+
+fn dh(local: &PKey<Private>, remote: &Key) -> Result<Vec<u8>, Error> {
+    let remote = PKey::public_key_from_raw_bytes(remote, Id::X25519).map_err(Error::BadKey)?;
+    let mut d = Deriver::new(local).map_err(Error::BadKey)?;
+    d.set_peer(&remote).map_err(Error::BadKey)?;
+    d.derive_to_vec().map_err(Error::BadKey)
+}
+
+fn transcript(e_i: &Key, s_i: &Key, e_r: &Key, s_r: &Key) -> Key {
+    let mut h = Sha256::new();
+    h.update(CONSTRUCTION);
+    h.update(e_i);
+    h.update(s_i);
+    h.update(e_r);
+    h.update(s_r);
+    h.finish()
+}
+
+/// HKDF with the transcript hash as info, output (confirm, i2r, r2i)
+fn derive(h: &Key, dh: &[&[u8]]) -> (Key, Key, Key) {
+    let prk = hmac(CONSTRUCTION, dh);
+    let t1 = hmac(&prk, &[h, &[1]]);
+    let t2 = hmac(&prk, &[&t1, h, &[2]]);
+    let t3 = hmac(&prk, &[&t2, h, &[3]]);
+    (t1, t2, t3)
+}
+
+fn mac(key: &Key, h: &Key) -> Result<[u8; TAG_LEN], Error> {
+    let mut tag = [0u8; TAG_LEN];
+    symm::encrypt_aead(
+        Cipher::chacha20_poly1305(),
+        key,
+        Some(&[0u8; 12]),
+        h,
+        &[],
+        &mut tag,
+    )
+    .map_err(Error::BadKey)?;
+    Ok(tag)
+}
+
+fn to_key(v: &[u8]) -> Option<Key> {
+    if v.len() != KEY_LEN {
+        return None;
+    }
+    let mut k = [0u8; KEY_LEN];
+    k.copy_from_slice(v);
+    Some(k)
+}
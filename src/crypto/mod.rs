@@ -0,0 +1,921 @@
+pub mod handshake;
+pub mod replay;
+pub mod session;
+
+use self::handshake::{Identity, Initiation};
+use self::replay::ReplayWindow;
+use self::session::{Keys, Session};
+use crate::config::Config;
+use crate::generated::transport::{Handshake, Handshake_Step, Payload};
+use crate::internal::buffer::Buffer;
+use crate::internal::queue::{self, Gauge};
+use log::*;
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sha::Sha256;
+use openssl::sign::Signer;
+use openssl::symm::{self, Cipher, Crypter, Mode};
+use protobuf::Message as ProtoMessage;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+pub const KEY_LEN: usize = 32;
+pub const TAG_LEN: usize = 16;
+
+/// envelope layout, all integer in network order
+///
+/// | 1 byte | 4 bytes | 8 bytes | ...        | 16 bytes |
+/// | type   | id      | counter | ciphertext | tag      |
+///
+/// the nonce is `id || counter`, the header is used as AAD.
+/// for `TYPE_HANDSHAKE` the id is the random sender id of the node and the key
+/// is derived from the network secret, for `TYPE_TRANSPORT` the id is the
+/// session index of the receiver and the key comes from the handshake.
+pub const HEADER_LEN: usize = 1 + 4 + 8;
+pub const OVERHEAD: usize = HEADER_LEN + TAG_LEN;
+
+pub const TYPE_HANDSHAKE: u8 = 1;
+pub const TYPE_TRANSPORT: u8 = 2;
+
+/// data-plane envelope of a package, a fixed header instead of protobuf
+///
+/// | 1 byte | 1 byte  | 1 byte | 1 byte | 4 bytes | 8 bytes | ...     | 16 bytes |
+/// | type   | version | ttl    | flags  | id      | counter | package | tag      |
+///
+/// it is sealed by the session like `TYPE_TRANSPORT` and share its counter,
+/// the nonce is `id || counter`, the header is used as AAD. flags is 0 now
+pub const TYPE_DATA: u8 = 3;
+pub const DATA_VERSION: u8 = 1;
+pub const DATA_HEADER_LEN: usize = 1 + 1 + 1 + 1 + 4 + 8;
+
+/// resend the initiation if no response after this
+const REKEY_TIMEOUT: Duration = Duration::from_secs(5);
+/// package wait for a session, drop the oldest one when full
+const MAX_QUEUED: usize = 128;
+/// the responder drop a session not confirmed by a transport package in it
+const UNCONFIRMED_TIMEOUT: Duration = Duration::from_secs(15);
+/// unconfirmed sessions of one static key, the oldest one is dropped for a
+/// new initiation, so repeated initiations can not take all the memory
+const MAX_UNCONFIRMED: usize = 4;
+
+pub type Key = [u8; KEY_LEN];
+
+#[derive(Debug)]
+pub enum Error {
+    TooShort(usize),
+    BadType(u8),
+    BadVersion(u8),
+    Replay(u32, u64),
+    Decrypt(ErrorStack),
+    BadKey(ErrorStack),
+    UnknownSession(u32),
+    UnknownPeer(SocketAddr),
+    Handshake(&'static str),
+}
+
+/// AEAD envelope around every datagram
+///
+/// handshake messages are sealed by the network secret, every node pick a
+/// random sender id when start up and the key of a sender is derived from the
+/// secret and its sender id, so no two node share a key.
+///
+/// everything else travel in a session set up by the handshake, the remote
+/// static key must be in the allow-list of the config.
+pub struct Crypto {
+    secret: Key,
+    local_id: u32,
+    local_key: Key,
+    counter: u64,
+    remotes: HashMap<u32, Remote>,
+
+    identity: Identity,
+    trusted: HashMap<Key, String>,
+
+    sessions: HashMap<u32, Session>,
+    endpoints: HashMap<SocketAddr, u32>,
+    initiations: HashMap<u32, Initiation>,
+    queue: HashMap<SocketAddr, VecDeque<Vec<u8>>>,
+
+    /// every one is bounded by `queue::CAPACITY` if the socket is not read
+    outgoing: VecDeque<(SocketAddr, Vec<u8>)>,
+    established: VecDeque<(SocketAddr, String)>,
+    roamed: VecDeque<(SocketAddr, SocketAddr)>,
+    dropped: Arc<Gauge>,
+}
+
+/// handshake envelope state of a sender id
+struct Remote {
+    key: Key,
+    window: ReplayWindow,
+}
+
+impl Crypto {
+    pub fn new(secret: &str, identity: Identity, trusted: HashMap<Key, String>) -> Self {
+        let secret = {
+            let mut h = Sha256::new();
+            h.update(secret.as_bytes());
+            h.finish()
+        };
+        let local_id = rand::random();
+        info!("local public key {}", identity.public_base64());
+        Self {
+            secret,
+            local_id,
+            local_key: derive_key(&secret, local_id),
+            counter: 0,
+            remotes: HashMap::new(),
+            identity,
+            trusted,
+            sessions: HashMap::new(),
+            endpoints: HashMap::new(),
+            initiations: HashMap::new(),
+            queue: HashMap::new(),
+            outgoing: VecDeque::new(),
+            established: VecDeque::new(),
+            roamed: VecDeque::new(),
+            dropped: queue::gauge("crypto outgoing"),
+        }
+    }
+
+    pub fn from_config(c: &Config) -> Self {
+        let identity = Identity::load_or_generate(&c.private_key_file)
+            .unwrap_or_else(|e| panic!("can not load {}, {}", c.private_key_file, e));
+        let mut trusted = HashMap::with_capacity(c.peers.len());
+        for p in &c.peers {
+            match handshake::public_from_base64(&p.public_key) {
+                Ok(k) => {
+                    trusted.insert(k, p.name.clone());
+                }
+                Err(e) => error!("ignore peer {}, {}", p.name, e),
+            }
+        }
+        if trusted.is_empty() {
+            warn!("no peer in the allow-list, no node can join");
+        }
+        Self::new(&c.secret, identity, trusted)
+    }
+
+    /// seal a plain datagram to `addr`, the result is read by `pop_outgoing`
+    ///
+    /// if there is no session with `addr` the datagram is queued and a
+    /// handshake is started
+    pub fn seal(&mut self, addr: SocketAddr, plain: &[u8]) {
+        if let Some(index) = self.endpoints.get(&addr) {
+            if let Some(session) = self.sessions.get(index) {
+                let package = session.seal(plain);
+                self.push_outgoing(addr, package);
+                return;
+            }
+        }
+
+        let queue = self.queue.entry(addr).or_default();
+        if queue.len() >= MAX_QUEUED {
+            queue.pop_front();
+        }
+        queue.push_back(plain.to_vec());
+        self.initiate(addr);
+    }
+
+    /// authenticate and decrypt a envelope from `addr`
+    ///
+    /// return `None` for handshake and keepalive, the counter is only recorded
+    /// after the tag is verified, so a forged package can not move the window
+    pub fn open(&mut self, addr: SocketAddr, package: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        if package.len() < OVERHEAD {
+            return Err(Error::TooShort(package.len()));
+        }
+        let id = read_u32(&package[1..5]);
+        let counter = read_u64(&package[5..13]);
+
+        match package[0] {
+            TYPE_HANDSHAKE => {
+                let plain = self.open_handshake(id, counter, package)?;
+                self.handshake(addr, &plain)?;
+                Ok(None)
+            }
+            TYPE_TRANSPORT => {
+                let session = self.sessions.get(&id).ok_or(Error::UnknownSession(id))?;
+                let plain = session.open(counter, package)?;
+                self.accept(addr, id);
+
+                if plain.is_empty() {
+                    trace!("keepalive from {}", addr);
+                    return Ok(None);
+                }
+                Ok(Some(plain))
+            }
+            t => Err(Error::BadType(t)),
+        }
+    }
+
+    /// datagrams waiting for a handshake
+    pub fn queued(&self) -> usize {
+        self.queue.values().map(|q| q.len()).sum()
+    }
+
+    pub fn pop_outgoing(&mut self) -> Option<(SocketAddr, Vec<u8>)> {
+        self.outgoing.pop_front()
+    }
+
+    /// peer finish the handshake, with the name in allow-list
+    pub fn pop_established(&mut self) -> Option<(SocketAddr, String)> {
+        self.established.pop_front()
+    }
+
+    /// peer send from a new address, the old and the new one
+    pub fn pop_roamed(&mut self) -> Option<(SocketAddr, SocketAddr)> {
+        self.roamed.pop_front()
+    }
+}
+
+impl Crypto {
+    fn initiate(&mut self, addr: SocketAddr) {
+        let in_flight = self
+            .initiations
+            .values()
+            .any(|i| i.addr == addr && i.sent.elapsed() < REKEY_TIMEOUT);
+        if in_flight {
+            return;
+        }
+        self.initiations.retain(|_, i| i.addr != addr);
+
+        let index = self.new_index();
+        info!("start handshake {:08x} with {}", index, addr);
+        let (state, m) = handshake::initiate(&self.identity, index, addr);
+        self.initiations.insert(index, state);
+        self.send_handshake(addr, m);
+    }
+
+    /// a transport package of session `index` from `addr` is authenticated,
+    /// confirm the session, or follow the peer to `addr`
+    fn accept(&mut self, addr: SocketAddr, index: u32) {
+        let session = match self.sessions.get_mut(&index) {
+            Some(session) => session,
+            None => return,
+        };
+        if !session.confirmed {
+            info!("session {:08x} with {} confirmed", index, session.name);
+            session.confirmed = true;
+            let (addr, name) = (session.addr, session.name.clone());
+            self.establish(addr, name, index);
+        } else if session.addr != addr {
+            info!("{} roam from {} to {}", session.name, session.addr, addr);
+            let old = std::mem::replace(&mut session.addr, addr);
+            self.roam(old, addr, index);
+        }
+    }
+
+    fn handshake(&mut self, addr: SocketAddr, plain: &[u8]) -> Result<(), Error> {
+        use crate::generated::transport::Payload_oneof_payload as PayloadOneof;
+
+        let mut payload = Payload::new();
+        payload
+            .merge_from_bytes(plain)
+            .map_err(|_| Error::Handshake("bad protobuf"))?;
+        let m = match payload.payload {
+            Some(PayloadOneof::handshake(m)) => m,
+            _ => return Err(Error::Handshake("not a handshake message")),
+        };
+
+        let name = self
+            .trusted
+            .get(m.get_static_key())
+            .cloned()
+            .ok_or(Error::UnknownPeer(addr))?;
+
+        match m.get_step() {
+            Handshake_Step::INITIATION => {
+                let index = self.new_index();
+                let (keys, response) = handshake::respond(&self.identity, &m, index)?;
+                info!("accept handshake from {} at {}", name, addr);
+                self.expire_unconfirmed(&name);
+                let session = Session::new(
+                    index,
+                    m.get_sender(),
+                    name,
+                    addr,
+                    false,
+                    keys.send,
+                    keys.recv,
+                );
+                self.sessions.insert(index, session);
+                self.send_handshake(addr, response);
+            }
+            Handshake_Step::RESPONSE => {
+                let index = m.get_receiver();
+                let keys = match self.initiations.get(&index) {
+                    Some(state) => handshake::complete(&self.identity, state, &m)?,
+                    None => return Err(Error::UnknownSession(index)),
+                };
+                self.initiations.remove(&index);
+                info!("handshake with {} at {} done", name, addr);
+                let session = Session::new(
+                    index,
+                    m.get_sender(),
+                    name.clone(),
+                    addr,
+                    true,
+                    keys.send,
+                    keys.recv,
+                );
+                self.sessions.insert(index, session);
+                self.establish(addr, name, index);
+
+                // the responder wait for a transport package to trust us
+                if !self.queue.contains_key(&addr) {
+                    self.seal(addr, &[]);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// drop the unconfirmed sessions timed out, and the oldest ones of `name`
+    /// to leave room for a new one
+    fn expire_unconfirmed(&mut self, name: &str) {
+        self.sessions
+            .retain(|_, s| s.confirmed || s.created.elapsed() < UNCONFIRMED_TIMEOUT);
+
+        let mut pending: Vec<(u32, Instant)> = self
+            .sessions
+            .iter()
+            .filter(|(_, s)| !s.confirmed && s.name == name)
+            .map(|(index, s)| (*index, s.created))
+            .collect();
+        if pending.len() < MAX_UNCONFIRMED {
+            return;
+        }
+        pending.sort_by_key(|(_, created)| *created);
+        for (index, _) in pending.iter().take(pending.len() + 1 - MAX_UNCONFIRMED) {
+            debug!("drop unconfirmed session {:08x} of {}", index, name);
+            self.sessions.remove(index);
+        }
+    }
+
+    /// make the session the one to use for `addr`, flush the queue
+    fn establish(&mut self, addr: SocketAddr, name: String, index: u32) {
+        if let Some(old) = self.endpoints.insert(addr, index) {
+            if old != index {
+                self.sessions.remove(&old);
+            }
+        }
+        if self.established.len() < queue::CAPACITY {
+            self.established.push_back((addr, name));
+        } else {
+            warn!("socket is stalled, the handshake with {} is not told", name);
+        }
+
+        if let Some(queue) = self.queue.remove(&addr) {
+            for plain in queue {
+                self.seal(addr, &plain);
+            }
+        }
+    }
+
+    /// a authenticated package of session `index` come from `new`, send to
+    /// it from now on, same as the roaming of wireguard
+    fn roam(&mut self, old: SocketAddr, new: SocketAddr, index: u32) {
+        if self.endpoints.get(&old) == Some(&index) {
+            self.endpoints.remove(&old);
+        }
+        if let Some(other) = self.endpoints.insert(new, index) {
+            if other != index {
+                self.sessions.remove(&other);
+            }
+        }
+        if self.roamed.len() < queue::CAPACITY {
+            self.roamed.push_back((old, new));
+        } else {
+            warn!("socket is stalled, the roaming to {} is not told", new);
+        }
+
+        if let Some(queue) = self.queue.remove(&new) {
+            for plain in queue {
+                self.seal(new, &plain);
+            }
+        }
+    }
+
+    fn send_handshake(&mut self, addr: SocketAddr, m: Handshake) {
+        let mut payload = Payload::new();
+        payload.set_handshake(m);
+        let plain = payload.write_to_bytes().unwrap();
+
+        let counter = self.counter;
+        self.counter += 1;
+        let package = seal_envelope(
+            TYPE_HANDSHAKE,
+            self.local_id,
+            counter,
+            &self.local_key,
+            &plain,
+        );
+        self.push_outgoing(addr, package);
+    }
+
+    /// drop at the tail if the socket is stalled, a handshake is sent again
+    /// after `REKEY_TIMEOUT`
+    fn push_outgoing(&mut self, addr: SocketAddr, package: Vec<u8>) {
+        if self.outgoing.len() >= queue::CAPACITY {
+            self.dropped.drop_data();
+            return;
+        }
+        self.outgoing.push_back((addr, package));
+    }
+
+    fn open_handshake(
+        &mut self,
+        sender: u32,
+        counter: u64,
+        package: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        let secret = &self.secret;
+        let remote = self.remotes.entry(sender).or_insert_with(|| Remote {
+            key: derive_key(secret, sender),
+            window: ReplayWindow::new(),
+        });
+
+        if !remote.window.check(counter) {
+            return Err(Error::Replay(sender, counter));
+        }
+
+        match open_envelope(&remote.key, package) {
+            Ok(plain) => {
+                remote.window.update(counter);
+                Ok(plain)
+            }
+            Err(e) => {
+                // do not keep state for sender id nobody can prove
+                if remote.window.is_empty() {
+                    self.remotes.remove(&sender);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    fn new_index(&self) -> u32 {
+        loop {
+            let index = rand::random();
+            if !self.sessions.contains_key(&index) && !self.initiations.contains_key(&index) {
+                return index;
+            }
+        }
+    }
+}
+
+/// `Crypto` shared by the workers
+///
+/// the AEAD of a transport package run outside the lock with the `Keys` of
+/// the session, the lock is only held to look it up, and for the handshake
+#[derive(Clone)]
+pub struct Shared(Arc<Mutex<Crypto>>);
+
+impl Shared {
+    pub fn new(crypto: Crypto) -> Self {
+        Shared(Arc::new(Mutex::new(crypto)))
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, Crypto> {
+        self.0.lock().unwrap()
+    }
+
+    /// seal a plain datagram to `addr` if there is a session, or it is queued
+    /// in `Crypto` and read by `pop_outgoing` after the handshake
+    pub fn seal(&self, addr: SocketAddr, plain: &[u8]) -> Option<Vec<u8>> {
+        let keys = {
+            let mut crypto = self.lock();
+            let session = crypto
+                .endpoints
+                .get(&addr)
+                .and_then(|index| crypto.sessions.get(index));
+            match session {
+                Some(session) => session.keys(),
+                None => {
+                    crypto.seal(addr, plain);
+                    return None;
+                }
+            }
+        };
+        Some(keys.seal(plain))
+    }
+
+    /// same as `Crypto::open`
+    pub fn open(&self, addr: SocketAddr, package: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        if package.len() < OVERHEAD || package[0] != TYPE_TRANSPORT {
+            return self.lock().open(addr, package);
+        }
+        let id = read_u32(&package[1..5]);
+        let counter = read_u64(&package[5..13]);
+
+        let (keys, accepted) = self.keys_of(id, addr)?;
+        let plain = keys.open(counter, package)?;
+        if !accepted {
+            self.lock().accept(addr, id);
+        }
+
+        if plain.is_empty() {
+            trace!("keepalive from {}", addr);
+            return Ok(None);
+        }
+        Ok(Some(plain))
+    }
+
+    /// seal `package` with the data-plane header if there is a session with
+    /// `addr`, or give it back
+    pub fn seal_data(&self, addr: SocketAddr, ttl: u8, package: Buffer) -> Result<Buffer, Buffer> {
+        let keys = {
+            let crypto = self.lock();
+            let session = crypto
+                .endpoints
+                .get(&addr)
+                .and_then(|index| crypto.sessions.get(index));
+            match session {
+                Some(session) => session.keys(),
+                None => return Err(package),
+            }
+        };
+        Ok(keys.seal_data(ttl, &package))
+    }
+
+    /// open a `TYPE_DATA` envelope, return the package and the TTL
+    pub fn open_data(&self, addr: SocketAddr, package: &[u8]) -> Result<(Buffer, u8), Error> {
+        if package.len() < DATA_HEADER_LEN + TAG_LEN {
+            return Err(Error::TooShort(package.len()));
+        }
+        if package[1] != DATA_VERSION {
+            return Err(Error::BadVersion(package[1]));
+        }
+        let id = read_u32(&package[4..8]);
+
+        let (keys, accepted) = self.keys_of(id, addr)?;
+        let opened = keys.open_data(package)?;
+        if !accepted {
+            self.lock().accept(addr, id);
+        }
+        Ok(opened)
+    }
+
+    /// the keys of session `id`, and true if it is confirmed at `addr`
+    fn keys_of(&self, id: u32, addr: SocketAddr) -> Result<(Arc<Keys>, bool), Error> {
+        let crypto = self.lock();
+        let session = crypto.sessions.get(&id).ok_or(Error::UnknownSession(id))?;
+        Ok((session.keys(), session.confirmed && session.addr == addr))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::TooShort(len) => write!(f, "envelope too short, {} bytes", len),
+            Error::BadType(t) => write!(f, "unknown envelope type {}", t),
+            Error::BadVersion(v) => write!(f, "unknown data header version {}", v),
+            Error::Replay(id, counter) => write!(f, "replayed counter {} of {:08x}", counter, id),
+            Error::Decrypt(e) => write!(f, "decrypt error {}", e),
+            Error::BadKey(e) => write!(f, "key error {}", e),
+            Error::UnknownSession(id) => write!(f, "unknown session {:08x}", id),
+            Error::UnknownPeer(addr) => write!(f, "static key of {} not in allow-list", addr),
+            Error::Handshake(e) => write!(f, "handshake error, {}", e),
+        }
+    }
+}
+
+/// seal `plain` into a envelope, see `HEADER_LEN` for the layout
+pub(crate) fn seal_envelope(t: u8, id: u32, counter: u64, key: &Key, plain: &[u8]) -> Vec<u8> {
+    let mut header = [0u8; HEADER_LEN];
+    header[0] = t;
+    header[1..5].copy_from_slice(&id.to_be_bytes());
+    header[5..13].copy_from_slice(&counter.to_be_bytes());
+
+    let mut tag = [0u8; TAG_LEN];
+    let cipher = symm::encrypt_aead(
+        Cipher::chacha20_poly1305(),
+        key,
+        Some(&header[1..]),
+        &header,
+        plain,
+        &mut tag,
+    )
+    .expect("chacha20-poly1305 encrypt");
+
+    let mut v = Vec::with_capacity(OVERHEAD + cipher.len());
+    v.extend_from_slice(&header);
+    v.extend_from_slice(&cipher);
+    v.extend_from_slice(&tag);
+    v
+}
+
+/// verify and decrypt a envelope, the length is checked by the caller
+pub(crate) fn open_envelope(key: &Key, package: &[u8]) -> Result<Vec<u8>, Error> {
+    let (header, body) = package.split_at(HEADER_LEN);
+    let (cipher, tag) = body.split_at(body.len() - TAG_LEN);
+    symm::decrypt_aead(
+        Cipher::chacha20_poly1305(),
+        key,
+        Some(&header[1..]),
+        header,
+        cipher,
+        tag,
+    )
+    .map_err(Error::Decrypt)
+}
+
+/// seal `plain` into a pool buffer with the data header in its headroom, see
+/// `DATA_HEADER_LEN` for the layout
+pub(crate) fn seal_data_envelope(
+    id: u32,
+    counter: u64,
+    ttl: u8,
+    key: &Key,
+    plain: &[u8],
+) -> Buffer {
+    // the cipher may write a block more than the input before finalize
+    let mut out = Buffer::with_len(plain.len() + TAG_LEN);
+    let header = {
+        let mut header = [0u8; DATA_HEADER_LEN];
+        header[0] = TYPE_DATA;
+        header[1] = DATA_VERSION;
+        header[2] = ttl;
+        header[4..8].copy_from_slice(&id.to_be_bytes());
+        header[8..16].copy_from_slice(&counter.to_be_bytes());
+        header
+    };
+
+    let mut c = Crypter::new(
+        Cipher::chacha20_poly1305(),
+        Mode::Encrypt,
+        key,
+        Some(&header[4..]),
+    )
+    .expect("chacha20-poly1305 encrypt");
+    c.aad_update(&header).expect("chacha20-poly1305 encrypt");
+    let mut n = c
+        .update(plain, &mut out)
+        .expect("chacha20-poly1305 encrypt");
+    n += c
+        .finalize(&mut out[n..])
+        .expect("chacha20-poly1305 encrypt");
+    out.set_len(n);
+    c.get_tag(out.push_back(TAG_LEN))
+        .expect("chacha20-poly1305 encrypt");
+    out.push_front(DATA_HEADER_LEN).copy_from_slice(&header);
+    out
+}
+
+/// verify and decrypt a data envelope into a pool buffer, return it with
+/// the TTL, the length and version is checked by the caller
+pub(crate) fn open_data_envelope(key: &Key, package: &[u8]) -> Result<(Buffer, u8), Error> {
+    let (header, body) = package.split_at(DATA_HEADER_LEN);
+    let (cipher, tag) = body.split_at(body.len() - TAG_LEN);
+    let mut out = Buffer::with_len(cipher.len() + 1);
+
+    let mut c = Crypter::new(
+        Cipher::chacha20_poly1305(),
+        Mode::Decrypt,
+        key,
+        Some(&header[4..]),
+    )
+    .map_err(Error::Decrypt)?;
+    c.aad_update(header).map_err(Error::Decrypt)?;
+    let mut n = c.update(cipher, &mut out).map_err(Error::Decrypt)?;
+    c.set_tag(tag).map_err(Error::Decrypt)?;
+    n += c.finalize(&mut out[n..]).map_err(Error::Decrypt)?;
+    out.set_len(n);
+    Ok((out, header[2]))
+}
+
+/// HMAC-SHA256 over the concatenation of `data`
+pub(crate) fn hmac(key: &[u8], data: &[&[u8]]) -> Key {
+    let key = PKey::hmac(key).expect("hmac key");
+    let mut s = Signer::new(MessageDigest::sha256(), &key).expect("hmac signer");
+    for d in data {
+        s.update(d).expect("hmac update");
+    }
+    let mut out = [0u8; KEY_LEN];
+    s.sign(&mut out).expect("hmac sign");
+    out
+}
+
+fn derive_key(secret: &Key, sender: u32) -> Key {
+    let mut h = Sha256::new();
+    h.update(b"placeholder sender key");
+    h.update(secret);
+    h.update(&sender.to_be_bytes());
+    h.finish()
+}
+
+fn read_u32(b: &[u8]) -> u32 {
+    let mut t = [0u8; 4];
+    t.copy_from_slice(&b[..4]);
+    u32::from_be_bytes(t)
+}
+
+pub(crate) fn read_u64(b: &[u8]) -> u64 {
+    let mut t = [0u8; 8];
+    t.copy_from_slice(&b[..8]);
+    u64::from_be_bytes(t)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pair() -> (Crypto, SocketAddr, Crypto, SocketAddr) {
+        let a = Identity::generate();
+        let b = Identity::generate();
+        let mut trust_a = HashMap::new();
+        trust_a.insert(*b.public_key(), "b".to_string());
+        let mut trust_b = HashMap::new();
+        trust_b.insert(*a.public_key(), "a".to_string());
+        (
+            Crypto::new("secret", a, trust_a),
+            "127.0.0.1:1000".parse().unwrap(),
+            Crypto::new("secret", b, trust_b),
+            "127.0.0.1:2000".parse().unwrap(),
+        )
+    }
+
+    /// deliver everything in `from` outgoing queue to `to`, return the payloads
+    fn deliver(from: &mut Crypto, from_addr: SocketAddr, to: &mut Crypto) -> Vec<Vec<u8>> {
+        let mut v = vec![];
+        while let Some((_, package)) = from.pop_outgoing() {
+            if let Some(plain) = to.open(from_addr, &package).unwrap() {
+                v.push(plain);
+            }
+        }
+        v
+    }
+
+    #[test]
+    pub fn handshake_then_transport() {
+        let (mut a, a_addr, mut b, b_addr) = pair();
+
+        a.seal(b_addr, b"hello");
+        assert!(deliver(&mut a, a_addr, &mut b).is_empty());
+        assert!(deliver(&mut b, b_addr, &mut a).is_empty());
+        assert_eq!(a.pop_established(), Some((b_addr, "b".to_string())));
+        assert_eq!(b.pop_established(), None);
+
+        assert_eq!(deliver(&mut a, a_addr, &mut b), vec![b"hello".to_vec()]);
+        assert_eq!(b.pop_established(), Some((a_addr, "a".to_string())));
+
+        b.seal(a_addr, b"world");
+        assert_eq!(deliver(&mut b, b_addr, &mut a), vec![b"world".to_vec()]);
+    }
+
+    #[test]
+    pub fn bounded_outgoing() {
+        let (mut a, a_addr, mut b, b_addr) = pair();
+        a.seal(b_addr, b"hello");
+        deliver(&mut a, a_addr, &mut b);
+        deliver(&mut b, b_addr, &mut a);
+        while a.pop_outgoing().is_some() {}
+
+        // nobody read it
+        for _ in 0..queue::CAPACITY + 10 {
+            a.seal(b_addr, b"stalled");
+        }
+        assert_eq!(a.outgoing.len(), queue::CAPACITY);
+        assert_eq!(a.dropped.dropped(), 10);
+    }
+
+    #[test]
+    pub fn roaming() {
+        let (mut a, a_addr, mut b, b_addr) = pair();
+        a.seal(b_addr, b"hello");
+        deliver(&mut a, a_addr, &mut b);
+        deliver(&mut b, b_addr, &mut a);
+        deliver(&mut a, a_addr, &mut b);
+
+        // b change its network
+        let roam_addr = "127.0.0.2:3000".parse().unwrap();
+        b.seal(a_addr, b"moved");
+        assert_eq!(deliver(&mut b, roam_addr, &mut a), vec![b"moved".to_vec()]);
+        assert_eq!(a.pop_roamed(), Some((b_addr, roam_addr)));
+        assert_eq!(a.pop_roamed(), None);
+
+        // no new handshake to the new address
+        a.seal(roam_addr, b"follow");
+        let (to, package) = a.pop_outgoing().unwrap();
+        assert_eq!(to, roam_addr);
+        assert_eq!(b.open(a_addr, &package).unwrap(), Some(b"follow".to_vec()));
+    }
+
+    #[test]
+    pub fn shared_by_workers() {
+        let (mut a, a_addr, b, b_addr) = pair();
+        let b = Shared::new(b);
+
+        a.seal(b_addr, b"hello");
+        while let Some((_, package)) = a.pop_outgoing() {
+            b.open(a_addr, &package).unwrap();
+        }
+        while let Some((_, package)) = b.lock().pop_outgoing() {
+            a.open(b_addr, &package).unwrap();
+        }
+        let (_, package) = a.pop_outgoing().unwrap();
+
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let (b, package) = (b.clone(), package.clone());
+                std::thread::spawn(move || b.open(a_addr, &package).is_ok())
+            })
+            .collect();
+        let opened = workers
+            .into_iter()
+            .map(|w| w.join().unwrap())
+            .filter(|ok| *ok)
+            .count();
+        assert_eq!(opened, 1);
+        assert_eq!(b.lock().pop_established(), Some((a_addr, "a".to_string())));
+
+        let package = b.seal(a_addr, b"world").unwrap();
+        assert_eq!(a.open(b_addr, &package).unwrap(), Some(b"world".to_vec()));
+    }
+
+    #[test]
+    pub fn cap_unconfirmed() {
+        let (mut a, a_addr, mut b, b_addr) = pair();
+        for _ in 0..10 {
+            a.initiations.clear();
+            a.initiate(b_addr);
+            let (_, init) = a.pop_outgoing().unwrap();
+            b.open(a_addr, &init).unwrap();
+        }
+        assert_eq!(b.sessions.len(), MAX_UNCONFIRMED);
+
+        // the latest one still work
+        while let Some((_, p)) = b.pop_outgoing() {
+            let _ = a.open(b_addr, &p);
+        }
+        while let Some((_, p)) = a.pop_outgoing() {
+            b.open(a_addr, &p).unwrap();
+        }
+        assert_eq!(b.pop_established(), Some((a_addr, "a".to_string())));
+    }
+
+    #[test]
+    pub fn data_header() {
+        let (a, a_addr, b, b_addr) = pair();
+        let (a, b) = (Shared::new(a), Shared::new(b));
+
+        // no session yet, the package is given back
+        let package = Buffer::from(vec![0x45; 100]);
+        let package = a.seal_data(b_addr, 64, package).err().unwrap();
+
+        a.lock().seal(b_addr, b"hello");
+        assert_eq!(a.lock().queued(), 1);
+        while let Some((_, p)) = a.lock().pop_outgoing() {
+            b.open(a_addr, &p).unwrap();
+        }
+        while let Some((_, p)) = b.lock().pop_outgoing() {
+            a.open(b_addr, &p).unwrap();
+        }
+        assert_eq!(a.lock().queued(), 0);
+
+        let sealed = a.seal_data(b_addr, 64, package).ok().unwrap();
+        assert_eq!(sealed[0], TYPE_DATA);
+        assert_eq!(sealed.len(), DATA_HEADER_LEN + 100 + TAG_LEN);
+        let (plain, ttl) = b.open_data(a_addr, &sealed).unwrap();
+        assert_eq!((&plain[..], ttl), (&[0x45; 100][..], 64));
+        match b.open_data(a_addr, &sealed) {
+            Err(Error::Replay(_, _)) => {}
+            _ => panic!("must reject replay"),
+        }
+
+        let mut unknown = sealed.to_vec();
+        unknown[1] = DATA_VERSION + 1;
+        match b.open_data(a_addr, &unknown) {
+            Err(Error::BadVersion(_)) => {}
+            _ => panic!("must reject unknown version"),
+        }
+    }
+
+    #[test]
+    pub fn reject_untrusted_and_replay() {
+        let (mut a, a_addr, mut b, b_addr) = pair();
+        b.trusted.clear();
+
+        a.seal(b_addr, b"hello");
+        let (_, init) = a.pop_outgoing().unwrap();
+        match b.open(a_addr, &init) {
+            Err(Error::Replay(_, _)) | Ok(_) => panic!("must reject untrusted key"),
+            Err(_) => {}
+        }
+
+        let (mut a, a_addr, mut b, b_addr) = pair();
+        a.seal(b_addr, b"hello");
+        deliver(&mut a, a_addr, &mut b);
+        deliver(&mut b, b_addr, &mut a);
+        let (_, package) = a.pop_outgoing().unwrap();
+        assert!(b.open(a_addr, &package).unwrap().is_some());
+        match b.open(a_addr, &package) {
+            Err(Error::Replay(_, _)) => {}
+            _ => panic!("must reject replay"),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+/// number of counters tracked behind the highest one seen
+///
+/// one block is always reserved for the block currently being filled,
+/// so the usable window is `WINDOW_SIZE - 64`
+const WINDOW_SIZE: u64 = 2048;
+const BLOCKS: usize = (WINDOW_SIZE / 64) as usize;
+
+/// sliding window bitmap for replay rejection, same idea as RFC 6479
+#[derive(Debug, Clone)]
+pub struct ReplayWindow {
+    top: u64,
+    bitmap: [u64; BLOCKS],
+}
+
+impl Default for ReplayWindow {
+    fn default() -> Self {
+        Self {
+            top: 0,
+            bitmap: [0; BLOCKS],
+        }
+    }
+}
+
+impl ReplayWindow {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// return true if `counter` is not seen yet and still inside the window
+    ///
+    /// do not modify the window, call `update` after the package is authenticated
+    pub fn check(&self, counter: u64) -> bool {
+        // the counter is from the wire, not authenticated yet
+        if counter.saturating_add(WINDOW_SIZE - 64) <= self.top {
+            return false;
+        }
+        if counter > self.top {
+            return true;
+        }
+        let (block, bit) = Self::position(counter);
+        self.bitmap[block] & (1 << bit) == 0
+    }
+
+    /// mark `counter` as seen, return false if it is a replay
+    pub fn update(&mut self, counter: u64) -> bool {
+        if !self.check(counter) {
+            return false;
+        }
+
+        if counter > self.top {
+            let current = self.top / 64;
+            let target = counter / 64;
+            let clear = (target - current).min(BLOCKS as u64);
+            for i in 1..=clear {
+                self.bitmap[((current + i) % BLOCKS as u64) as usize] = 0;
+            }
+            self.top = counter;
+        }
+
+        let (block, bit) = Self::position(counter);
+        self.bitmap[block] |= 1 << bit;
+        true
+    }
+
+    /// no counter is recorded yet
+    pub fn is_empty(&self) -> bool {
+        self.top == 0 && self.bitmap.iter().all(|b| *b == 0)
+    }
+
+    #[inline]
+    fn position(counter: u64) -> (usize, u64) {
+        (((counter / 64) % BLOCKS as u64) as usize, counter % 64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn reject_duplicate() {
+        let mut w = ReplayWindow::new();
+        assert!(w.update(0));
+        assert!(!w.update(0));
+        assert!(w.update(1));
+        assert!(!w.update(1));
+    }
+
+    #[test]
+    pub fn accept_out_of_order() {
+        let mut w = ReplayWindow::new();
+        assert!(w.update(10));
+        assert!(w.update(3));
+        assert!(w.update(7));
+        assert!(!w.update(3));
+        assert!(w.update(11));
+    }
+
+    #[test]
+    pub fn reject_too_old() {
+        let mut w = ReplayWindow::new();
+        assert!(w.update(1));
+        assert!(w.update(WINDOW_SIZE * 3));
+        assert!(!w.check(1));
+        assert!(!w.update(WINDOW_SIZE));
+        assert!(w.update(WINDOW_SIZE * 3 - 10));
+    }
+
+    #[test]
+    pub fn clear_on_jump() {
+        let mut w = ReplayWindow::new();
+        assert!(w.update(64));
+        // jump a whole window, the slot of 64 is reused by 64 + WINDOW_SIZE
+        assert!(w.update(64 + WINDOW_SIZE));
+        assert!(!w.update(64));
+        assert!(!w.update(64 + WINDOW_SIZE));
+    }
+
+    #[test]
+    pub fn counter_at_max() {
+        let mut w = ReplayWindow::new();
+        assert!(w.update(WINDOW_SIZE * 3));
+        // must not overflow
+        assert!(w.check(u64::MAX));
+        assert!(w.update(u64::MAX));
+        assert!(!w.check(u64::MAX));
+        assert!(!w.check(WINDOW_SIZE * 3));
+    }
+}
@@ -0,0 +1,121 @@
+use super::replay::ReplayWindow;
+use super::{
+    open_data_envelope, open_envelope, read_u64, seal_data_envelope, seal_envelope, Error, Key,
+    TYPE_TRANSPORT,
+};
+use crate::internal::buffer::Buffer;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// a session set up by the handshake
+///
+/// transport package carry the index of the receiver side, so a package can
+/// be matched with its session without looking at the source address
+pub struct Session {
+    /// our index, written by the remote in every transport package
+    pub local: u32,
+    /// the index of the remote, write it in every package we send
+    pub remote: u32,
+    /// the name of the remote in the allow-list
+    pub name: String,
+    pub addr: SocketAddr,
+    /// the responder only trust the session after the first valid transport
+    /// package, that package prove the initiator own its static key
+    pub confirmed: bool,
+    /// a unconfirmed session is dropped if it is not confirmed in time
+    pub created: Instant,
+
+    keys: Arc<Keys>,
+}
+
+/// the transport state of a session, a worker clone it out of `Crypto` and
+/// seal or open without holding the lock
+pub struct Keys {
+    local: u32,
+    remote: u32,
+    send: Key,
+    recv: Key,
+    counter: AtomicU64,
+    window: Mutex<ReplayWindow>,
+}
+
+impl Session {
+    pub fn new(
+        local: u32,
+        remote: u32,
+        name: String,
+        addr: SocketAddr,
+        confirmed: bool,
+        send: Key,
+        recv: Key,
+    ) -> Self {
+        Self {
+            local,
+            remote,
+            name,
+            addr,
+            confirmed,
+            created: Instant::now(),
+            keys: Arc::new(Keys {
+                local,
+                remote,
+                send,
+                recv,
+                counter: AtomicU64::new(0),
+                window: Mutex::new(ReplayWindow::new()),
+            }),
+        }
+    }
+
+    pub fn keys(&self) -> Arc<Keys> {
+        self.keys.clone()
+    }
+
+    pub fn seal(&self, plain: &[u8]) -> Vec<u8> {
+        self.keys.seal(plain)
+    }
+
+    pub fn open(&self, counter: u64, package: &[u8]) -> Result<Vec<u8>, Error> {
+        self.keys.open(counter, package)
+    }
+}
+
+impl Keys {
+    pub fn seal(&self, plain: &[u8]) -> Vec<u8> {
+        let counter = self.counter.fetch_add(1, Ordering::Relaxed);
+        seal_envelope(TYPE_TRANSPORT, self.remote, counter, &self.send, plain)
+    }
+
+    pub fn open(&self, counter: u64, package: &[u8]) -> Result<Vec<u8>, Error> {
+        if !self.window.lock().unwrap().check(counter) {
+            return Err(Error::Replay(self.local, counter));
+        }
+        let plain = open_envelope(&self.recv, package)?;
+        // other worker may open the same counter meanwhile, only one win
+        if !self.window.lock().unwrap().update(counter) {
+            return Err(Error::Replay(self.local, counter));
+        }
+        Ok(plain)
+    }
+
+    /// seal a package with the data header, it share the counter with `seal`
+    pub fn seal_data(&self, ttl: u8, package: &[u8]) -> Buffer {
+        let counter = self.counter.fetch_add(1, Ordering::Relaxed);
+        seal_data_envelope(self.remote, counter, ttl, &self.send, package)
+    }
+
+    /// open a data envelope, return the package and the TTL
+    pub fn open_data(&self, package: &[u8]) -> Result<(Buffer, u8), Error> {
+        let counter = read_u64(&package[8..16]);
+        if !self.window.lock().unwrap().check(counter) {
+            return Err(Error::Replay(self.local, counter));
+        }
+        let opened = open_data_envelope(&self.recv, package)?;
+        if !self.window.lock().unwrap().update(counter) {
+            return Err(Error::Replay(self.local, counter));
+        }
+        Ok(opened)
+    }
+}
@@ -1,11 +1,12 @@
-// This file is generated by rust-protobuf 2.6.0. Do not edit
+// This file is generated by rust-protobuf 2.28.0. Do not edit
 // @generated
 
-// https://github.com/Manishearth/rust-clippy/issues/702
+// https://github.com/rust-lang/rust-clippy/issues/702
 #![allow(unknown_lints)]
-#![allow(clippy)]
+#![allow(clippy::all)]
 
-#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_attributes)]
+#![cfg_attr(rustfmt, rustfmt::skip)]
 
 #![allow(box_pointers)]
 #![allow(dead_code)]
@@ -14,12 +15,13 @@
 #![allow(non_snake_case)]
 #![allow(non_upper_case_globals)]
 #![allow(trivial_casts)]
-#![allow(unsafe_code)]
 #![allow(unused_imports)]
 #![allow(unused_results)]
+//! Generated file from `transport.proto`
 
-use protobuf::Message as Message_imported_for_functions;
-use protobuf::ProtobufEnum as ProtobufEnum_imported_for_functions;
+/// Generated files are compatible only with the same version
+/// of protobuf runtime.
+// const _PROTOBUF_VERSION_CHECK: () = ::protobuf::VERSION_2_28_0;
 
 #[derive(PartialEq,Clone,Default)]
 pub struct PackageShard {
@@ -89,7 +91,7 @@ impl ::protobuf::Message for PackageShard {
         true
     }
 
-    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
         while !is.eof()? {
             let (field_number, wire_type) = is.read_tag_unpack()?;
             match field_number {
@@ -126,7 +128,7 @@ impl ::protobuf::Message for PackageShard {
         my_size
     }
 
-    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
         if self.ttl != 0 {
             os.write_uint32(1, self.ttl)?;
         }
@@ -149,13 +151,13 @@ impl ::protobuf::Message for PackageShard {
         &mut self.unknown_fields
     }
 
-    fn as_any(&self) -> &::std::any::Any {
-        self as &::std::any::Any
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
     }
-    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
-        self as &mut ::std::any::Any
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
     }
-    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
         self
     }
 
@@ -168,40 +170,30 @@ impl ::protobuf::Message for PackageShard {
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
-        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
-            lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
-        };
-        unsafe {
-            descriptor.get(|| {
-                let mut fields = ::std::vec::Vec::new();
-                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
-                    "ttl",
-                    |m: &PackageShard| { &m.ttl },
-                    |m: &mut PackageShard| { &mut m.ttl },
-                ));
-                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
-                    "package",
-                    |m: &PackageShard| { &m.package },
-                    |m: &mut PackageShard| { &mut m.package },
-                ));
-                ::protobuf::reflect::MessageDescriptor::new::<PackageShard>(
-                    "PackageShard",
-                    fields,
-                    file_descriptor_proto()
-                )
-            })
-        }
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "ttl",
+                |m: &PackageShard| { &m.ttl },
+                |m: &mut PackageShard| { &mut m.ttl },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "package",
+                |m: &PackageShard| { &m.package },
+                |m: &mut PackageShard| { &mut m.package },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<PackageShard>(
+                "PackageShard",
+                fields,
+                file_descriptor_proto()
+            )
+        })
     }
 
     fn default_instance() -> &'static PackageShard {
-        static mut instance: ::protobuf::lazy::Lazy<PackageShard> = ::protobuf::lazy::Lazy {
-            lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const PackageShard,
-        };
-        unsafe {
-            instance.get(PackageShard::new)
-        }
+        static instance: ::protobuf::rt::LazyV2<PackageShard> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(PackageShard::new)
     }
 }
 
@@ -214,14 +206,14 @@ impl ::protobuf::Clear for PackageShard {
 }
 
 impl ::std::fmt::Debug for PackageShard {
-    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
 impl ::protobuf::reflect::ProtobufValue for PackageShard {
-    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
-        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
     }
 }
 
@@ -234,6 +226,8 @@ pub struct Node {
     pub jump: i32,
     pub real_ip: ::std::vec::Vec<u8>,
     pub port: i32,
+    pub metric: u32,
+    pub seqno: u32,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
@@ -372,6 +366,36 @@ impl Node {
     pub fn set_port(&mut self, v: i32) {
         self.port = v;
     }
+
+    // uint32 metric = 9;
+
+
+    pub fn get_metric(&self) -> u32 {
+        self.metric
+    }
+    pub fn clear_metric(&mut self) {
+        self.metric = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_metric(&mut self, v: u32) {
+        self.metric = v;
+    }
+
+    // uint32 seqno = 10;
+
+
+    pub fn get_seqno(&self) -> u32 {
+        self.seqno
+    }
+    pub fn clear_seqno(&mut self) {
+        self.seqno = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_seqno(&mut self, v: u32) {
+        self.seqno = v;
+    }
 }
 
 impl ::protobuf::Message for Node {
@@ -379,7 +403,7 @@ impl ::protobuf::Message for Node {
         true
     }
 
-    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
         while !is.eof()? {
             let (field_number, wire_type) = is.read_tag_unpack()?;
             match field_number {
@@ -413,6 +437,20 @@ impl ::protobuf::Message for Node {
                     let tmp = is.read_int32()?;
                     self.port = tmp;
                 },
+                9 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.metric = tmp;
+                },
+                10 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.seqno = tmp;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -443,12 +481,18 @@ impl ::protobuf::Message for Node {
         if self.port != 0 {
             my_size += ::protobuf::rt::value_size(8, self.port, ::protobuf::wire_format::WireTypeVarint);
         }
+        if self.metric != 0 {
+            my_size += ::protobuf::rt::value_size(9, self.metric, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.seqno != 0 {
+            my_size += ::protobuf::rt::value_size(10, self.seqno, ::protobuf::wire_format::WireTypeVarint);
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
     }
 
-    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
         if !self.sub_net.is_empty() {
             os.write_bytes(1, &self.sub_net)?;
         }
@@ -467,6 +511,12 @@ impl ::protobuf::Message for Node {
         if self.port != 0 {
             os.write_int32(8, self.port)?;
         }
+        if self.metric != 0 {
+            os.write_uint32(9, self.metric)?;
+        }
+        if self.seqno != 0 {
+            os.write_uint32(10, self.seqno)?;
+        }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -483,13 +533,13 @@ impl ::protobuf::Message for Node {
         &mut self.unknown_fields
     }
 
-    fn as_any(&self) -> &::std::any::Any {
-        self as &::std::any::Any
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
     }
-    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
-        self as &mut ::std::any::Any
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
     }
-    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
         self
     }
 
@@ -502,60 +552,60 @@ impl ::protobuf::Message for Node {
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
-        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
-            lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
-        };
-        unsafe {
-            descriptor.get(|| {
-                let mut fields = ::std::vec::Vec::new();
-                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
-                    "sub_net",
-                    |m: &Node| { &m.sub_net },
-                    |m: &mut Node| { &mut m.sub_net },
-                ));
-                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
-                    "net_mask",
-                    |m: &Node| { &m.net_mask },
-                    |m: &mut Node| { &mut m.net_mask },
-                ));
-                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
-                    "name",
-                    |m: &Node| { &m.name },
-                    |m: &mut Node| { &mut m.name },
-                ));
-                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
-                    "jump",
-                    |m: &Node| { &m.jump },
-                    |m: &mut Node| { &mut m.jump },
-                ));
-                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
-                    "real_ip",
-                    |m: &Node| { &m.real_ip },
-                    |m: &mut Node| { &mut m.real_ip },
-                ));
-                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
-                    "port",
-                    |m: &Node| { &m.port },
-                    |m: &mut Node| { &mut m.port },
-                ));
-                ::protobuf::reflect::MessageDescriptor::new::<Node>(
-                    "Node",
-                    fields,
-                    file_descriptor_proto()
-                )
-            })
-        }
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "sub_net",
+                |m: &Node| { &m.sub_net },
+                |m: &mut Node| { &mut m.sub_net },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "net_mask",
+                |m: &Node| { &m.net_mask },
+                |m: &mut Node| { &mut m.net_mask },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "name",
+                |m: &Node| { &m.name },
+                |m: &mut Node| { &mut m.name },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
+                "jump",
+                |m: &Node| { &m.jump },
+                |m: &mut Node| { &mut m.jump },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "real_ip",
+                |m: &Node| { &m.real_ip },
+                |m: &mut Node| { &mut m.real_ip },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeInt32>(
+                "port",
+                |m: &Node| { &m.port },
+                |m: &mut Node| { &mut m.port },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "metric",
+                |m: &Node| { &m.metric },
+                |m: &mut Node| { &mut m.metric },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "seqno",
+                |m: &Node| { &m.seqno },
+                |m: &mut Node| { &mut m.seqno },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Node>(
+                "Node",
+                fields,
+                file_descriptor_proto()
+            )
+        })
     }
 
     fn default_instance() -> &'static Node {
-        static mut instance: ::protobuf::lazy::Lazy<Node> = ::protobuf::lazy::Lazy {
-            lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const Node,
-        };
-        unsafe {
-            instance.get(Node::new)
-        }
+        static instance: ::protobuf::rt::LazyV2<Node> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Node::new)
     }
 }
 
@@ -567,19 +617,21 @@ impl ::protobuf::Clear for Node {
         self.jump = 0;
         self.real_ip.clear();
         self.port = 0;
+        self.metric = 0;
+        self.seqno = 0;
         self.unknown_fields.clear();
     }
 }
 
 impl ::std::fmt::Debug for Node {
-    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
 impl ::protobuf::reflect::ProtobufValue for Node {
-    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
-        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
     }
 }
 
@@ -587,6 +639,8 @@ impl ::protobuf::reflect::ProtobufValue for Node {
 pub struct PingPong {
     // message fields
     pub name: ::std::string::String,
+    pub sequence: u64,
+    pub reply: bool,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
@@ -628,6 +682,36 @@ impl PingPong {
     pub fn take_name(&mut self) -> ::std::string::String {
         ::std::mem::replace(&mut self.name, ::std::string::String::new())
     }
+
+    // uint64 sequence = 2;
+
+
+    pub fn get_sequence(&self) -> u64 {
+        self.sequence
+    }
+    pub fn clear_sequence(&mut self) {
+        self.sequence = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_sequence(&mut self, v: u64) {
+        self.sequence = v;
+    }
+
+    // bool reply = 3;
+
+
+    pub fn get_reply(&self) -> bool {
+        self.reply
+    }
+    pub fn clear_reply(&mut self) {
+        self.reply = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_reply(&mut self, v: bool) {
+        self.reply = v;
+    }
 }
 
 impl ::protobuf::Message for PingPong {
@@ -635,13 +719,27 @@ impl ::protobuf::Message for PingPong {
         true
     }
 
-    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
         while !is.eof()? {
             let (field_number, wire_type) = is.read_tag_unpack()?;
             match field_number {
                 1 => {
                     ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.name)?;
                 },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint64()?;
+                    self.sequence = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.reply = tmp;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -657,15 +755,27 @@ impl ::protobuf::Message for PingPong {
         if !self.name.is_empty() {
             my_size += ::protobuf::rt::string_size(1, &self.name);
         }
+        if self.sequence != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.sequence, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.reply != false {
+            my_size += 2;
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
     }
 
-    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
         if !self.name.is_empty() {
             os.write_string(1, &self.name)?;
         }
+        if self.sequence != 0 {
+            os.write_uint64(2, self.sequence)?;
+        }
+        if self.reply != false {
+            os.write_bool(3, self.reply)?;
+        }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -682,13 +792,13 @@ impl ::protobuf::Message for PingPong {
         &mut self.unknown_fields
     }
 
-    fn as_any(&self) -> &::std::any::Any {
-        self as &::std::any::Any
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
     }
-    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
-        self as &mut ::std::any::Any
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
     }
-    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
         self
     }
 
@@ -701,308 +811,2227 @@ impl ::protobuf::Message for PingPong {
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
-        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
-            lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
-        };
-        unsafe {
-            descriptor.get(|| {
-                let mut fields = ::std::vec::Vec::new();
-                fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
-                    "name",
-                    |m: &PingPong| { &m.name },
-                    |m: &mut PingPong| { &mut m.name },
-                ));
-                ::protobuf::reflect::MessageDescriptor::new::<PingPong>(
-                    "PingPong",
-                    fields,
-                    file_descriptor_proto()
-                )
-            })
-        }
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "name",
+                |m: &PingPong| { &m.name },
+                |m: &mut PingPong| { &mut m.name },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint64>(
+                "sequence",
+                |m: &PingPong| { &m.sequence },
+                |m: &mut PingPong| { &mut m.sequence },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "reply",
+                |m: &PingPong| { &m.reply },
+                |m: &mut PingPong| { &mut m.reply },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<PingPong>(
+                "PingPong",
+                fields,
+                file_descriptor_proto()
+            )
+        })
     }
 
     fn default_instance() -> &'static PingPong {
-        static mut instance: ::protobuf::lazy::Lazy<PingPong> = ::protobuf::lazy::Lazy {
-            lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const PingPong,
-        };
-        unsafe {
-            instance.get(PingPong::new)
-        }
+        static instance: ::protobuf::rt::LazyV2<PingPong> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(PingPong::new)
     }
 }
 
 impl ::protobuf::Clear for PingPong {
     fn clear(&mut self) {
         self.name.clear();
+        self.sequence = 0;
+        self.reply = false;
         self.unknown_fields.clear();
     }
 }
 
 impl ::std::fmt::Debug for PingPong {
-    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
 impl ::protobuf::reflect::ProtobufValue for PingPong {
-    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
-        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
     }
 }
 
 #[derive(PartialEq,Clone,Default)]
-pub struct Payload {
-    // message oneof groups
-    pub payload: ::std::option::Option<Payload_oneof_payload>,
+pub struct Handshake {
+    // message fields
+    pub step: Handshake_Step,
+    pub sender: u32,
+    pub receiver: u32,
+    pub ephemeral: ::std::vec::Vec<u8>,
+    pub static_key: ::std::vec::Vec<u8>,
+    pub mac: ::std::vec::Vec<u8>,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
 }
 
-impl<'a> ::std::default::Default for &'a Payload {
-    fn default() -> &'a Payload {
-        <Payload as ::protobuf::Message>::default_instance()
+impl<'a> ::std::default::Default for &'a Handshake {
+    fn default() -> &'a Handshake {
+        <Handshake as ::protobuf::Message>::default_instance()
     }
 }
 
-#[derive(Clone,PartialEq,Debug)]
-pub enum Payload_oneof_payload {
-    package(PackageShard),
-    add_node(Node),
-    del_node(Node),
-    ping(PingPong),
-}
-
-impl Payload {
-    pub fn new() -> Payload {
+impl Handshake {
+    pub fn new() -> Handshake {
         ::std::default::Default::default()
     }
 
-    // .PackageShard package = 1;
+    // .Handshake.Step step = 1;
 
 
-    pub fn get_package(&self) -> &PackageShard {
-        match self.payload {
-            ::std::option::Option::Some(Payload_oneof_payload::package(ref v)) => v,
-            _ => PackageShard::default_instance(),
-        }
-    }
-    pub fn clear_package(&mut self) {
-        self.payload = ::std::option::Option::None;
+    pub fn get_step(&self) -> Handshake_Step {
+        self.step
     }
-
-    pub fn has_package(&self) -> bool {
-        match self.payload {
-            ::std::option::Option::Some(Payload_oneof_payload::package(..)) => true,
-            _ => false,
-        }
+    pub fn clear_step(&mut self) {
+        self.step = Handshake_Step::INITIATION;
     }
 
     // Param is passed by value, moved
-    pub fn set_package(&mut self, v: PackageShard) {
-        self.payload = ::std::option::Option::Some(Payload_oneof_payload::package(v))
+    pub fn set_step(&mut self, v: Handshake_Step) {
+        self.step = v;
     }
 
-    // Mutable pointer to the field.
-    pub fn mut_package(&mut self) -> &mut PackageShard {
-        if let ::std::option::Option::Some(Payload_oneof_payload::package(_)) = self.payload {
-        } else {
-            self.payload = ::std::option::Option::Some(Payload_oneof_payload::package(PackageShard::new()));
-        }
-        match self.payload {
-            ::std::option::Option::Some(Payload_oneof_payload::package(ref mut v)) => v,
-            _ => panic!(),
-        }
+    // uint32 sender = 2;
+
+
+    pub fn get_sender(&self) -> u32 {
+        self.sender
+    }
+    pub fn clear_sender(&mut self) {
+        self.sender = 0;
     }
 
-    // Take field
-    pub fn take_package(&mut self) -> PackageShard {
-        if self.has_package() {
-            match self.payload.take() {
-                ::std::option::Option::Some(Payload_oneof_payload::package(v)) => v,
-                _ => panic!(),
-            }
-        } else {
-            PackageShard::new()
-        }
+    // Param is passed by value, moved
+    pub fn set_sender(&mut self, v: u32) {
+        self.sender = v;
     }
 
-    // .Node add_node = 3;
+    // uint32 receiver = 3;
 
 
-    pub fn get_add_node(&self) -> &Node {
-        match self.payload {
-            ::std::option::Option::Some(Payload_oneof_payload::add_node(ref v)) => v,
-            _ => Node::default_instance(),
-        }
+    pub fn get_receiver(&self) -> u32 {
+        self.receiver
     }
-    pub fn clear_add_node(&mut self) {
-        self.payload = ::std::option::Option::None;
+    pub fn clear_receiver(&mut self) {
+        self.receiver = 0;
     }
 
-    pub fn has_add_node(&self) -> bool {
-        match self.payload {
-            ::std::option::Option::Some(Payload_oneof_payload::add_node(..)) => true,
-            _ => false,
-        }
+    // Param is passed by value, moved
+    pub fn set_receiver(&mut self, v: u32) {
+        self.receiver = v;
+    }
+
+    // bytes ephemeral = 4;
+
+
+    pub fn get_ephemeral(&self) -> &[u8] {
+        &self.ephemeral
+    }
+    pub fn clear_ephemeral(&mut self) {
+        self.ephemeral.clear();
     }
 
     // Param is passed by value, moved
-    pub fn set_add_node(&mut self, v: Node) {
-        self.payload = ::std::option::Option::Some(Payload_oneof_payload::add_node(v))
+    pub fn set_ephemeral(&mut self, v: ::std::vec::Vec<u8>) {
+        self.ephemeral = v;
     }
 
     // Mutable pointer to the field.
-    pub fn mut_add_node(&mut self) -> &mut Node {
-        if let ::std::option::Option::Some(Payload_oneof_payload::add_node(_)) = self.payload {
-        } else {
-            self.payload = ::std::option::Option::Some(Payload_oneof_payload::add_node(Node::new()));
-        }
-        match self.payload {
-            ::std::option::Option::Some(Payload_oneof_payload::add_node(ref mut v)) => v,
-            _ => panic!(),
-        }
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_ephemeral(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.ephemeral
     }
 
     // Take field
-    pub fn take_add_node(&mut self) -> Node {
-        if self.has_add_node() {
-            match self.payload.take() {
-                ::std::option::Option::Some(Payload_oneof_payload::add_node(v)) => v,
-                _ => panic!(),
-            }
-        } else {
-            Node::new()
-        }
+    pub fn take_ephemeral(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.ephemeral, ::std::vec::Vec::new())
     }
 
-    // .Node del_node = 4;
+    // bytes static_key = 5;
 
 
-    pub fn get_del_node(&self) -> &Node {
-        match self.payload {
-            ::std::option::Option::Some(Payload_oneof_payload::del_node(ref v)) => v,
-            _ => Node::default_instance(),
-        }
+    pub fn get_static_key(&self) -> &[u8] {
+        &self.static_key
     }
-    pub fn clear_del_node(&mut self) {
-        self.payload = ::std::option::Option::None;
-    }
-
-    pub fn has_del_node(&self) -> bool {
-        match self.payload {
-            ::std::option::Option::Some(Payload_oneof_payload::del_node(..)) => true,
-            _ => false,
-        }
+    pub fn clear_static_key(&mut self) {
+        self.static_key.clear();
     }
 
     // Param is passed by value, moved
-    pub fn set_del_node(&mut self, v: Node) {
-        self.payload = ::std::option::Option::Some(Payload_oneof_payload::del_node(v))
+    pub fn set_static_key(&mut self, v: ::std::vec::Vec<u8>) {
+        self.static_key = v;
     }
 
     // Mutable pointer to the field.
-    pub fn mut_del_node(&mut self) -> &mut Node {
-        if let ::std::option::Option::Some(Payload_oneof_payload::del_node(_)) = self.payload {
-        } else {
-            self.payload = ::std::option::Option::Some(Payload_oneof_payload::del_node(Node::new()));
-        }
-        match self.payload {
-            ::std::option::Option::Some(Payload_oneof_payload::del_node(ref mut v)) => v,
-            _ => panic!(),
-        }
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_static_key(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.static_key
     }
 
     // Take field
-    pub fn take_del_node(&mut self) -> Node {
-        if self.has_del_node() {
-            match self.payload.take() {
-                ::std::option::Option::Some(Payload_oneof_payload::del_node(v)) => v,
-                _ => panic!(),
-            }
-        } else {
-            Node::new()
-        }
+    pub fn take_static_key(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.static_key, ::std::vec::Vec::new())
     }
 
-    // .PingPong ping = 7;
+    // bytes mac = 6;
 
 
-    pub fn get_ping(&self) -> &PingPong {
-        match self.payload {
-            ::std::option::Option::Some(Payload_oneof_payload::ping(ref v)) => v,
-            _ => PingPong::default_instance(),
-        }
-    }
-    pub fn clear_ping(&mut self) {
-        self.payload = ::std::option::Option::None;
+    pub fn get_mac(&self) -> &[u8] {
+        &self.mac
     }
-
-    pub fn has_ping(&self) -> bool {
-        match self.payload {
-            ::std::option::Option::Some(Payload_oneof_payload::ping(..)) => true,
-            _ => false,
-        }
+    pub fn clear_mac(&mut self) {
+        self.mac.clear();
     }
 
     // Param is passed by value, moved
-    pub fn set_ping(&mut self, v: PingPong) {
-        self.payload = ::std::option::Option::Some(Payload_oneof_payload::ping(v))
+    pub fn set_mac(&mut self, v: ::std::vec::Vec<u8>) {
+        self.mac = v;
     }
 
     // Mutable pointer to the field.
-    pub fn mut_ping(&mut self) -> &mut PingPong {
-        if let ::std::option::Option::Some(Payload_oneof_payload::ping(_)) = self.payload {
-        } else {
-            self.payload = ::std::option::Option::Some(Payload_oneof_payload::ping(PingPong::new()));
-        }
-        match self.payload {
-            ::std::option::Option::Some(Payload_oneof_payload::ping(ref mut v)) => v,
-            _ => panic!(),
-        }
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_mac(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.mac
     }
 
     // Take field
-    pub fn take_ping(&mut self) -> PingPong {
-        if self.has_ping() {
-            match self.payload.take() {
-                ::std::option::Option::Some(Payload_oneof_payload::ping(v)) => v,
-                _ => panic!(),
-            }
-        } else {
-            PingPong::new()
-        }
+    pub fn take_mac(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.mac, ::std::vec::Vec::new())
     }
 }
 
-impl ::protobuf::Message for Payload {
+impl ::protobuf::Message for Handshake {
     fn is_initialized(&self) -> bool {
-        if let Some(Payload_oneof_payload::package(ref v)) = self.payload {
-            if !v.is_initialized() {
-                return false;
-            }
-        }
-        if let Some(Payload_oneof_payload::add_node(ref v)) = self.payload {
-            if !v.is_initialized() {
-                return false;
-            }
-        }
-        if let Some(Payload_oneof_payload::del_node(ref v)) = self.payload {
-            if !v.is_initialized() {
-                return false;
-            }
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_proto3_enum_with_unknown_fields_into(wire_type, is, &mut self.step, 1, &mut self.unknown_fields)?
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.sender = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.receiver = tmp;
+                },
+                4 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.ephemeral)?;
+                },
+                5 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.static_key)?;
+                },
+                6 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.mac)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.step != Handshake_Step::INITIATION {
+            my_size += ::protobuf::rt::enum_size(1, self.step);
+        }
+        if self.sender != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.sender, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.receiver != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.receiver, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.ephemeral.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(4, &self.ephemeral);
+        }
+        if !self.static_key.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(5, &self.static_key);
+        }
+        if !self.mac.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(6, &self.mac);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.step != Handshake_Step::INITIATION {
+            os.write_enum(1, ::protobuf::ProtobufEnum::value(&self.step))?;
+        }
+        if self.sender != 0 {
+            os.write_uint32(2, self.sender)?;
+        }
+        if self.receiver != 0 {
+            os.write_uint32(3, self.receiver)?;
+        }
+        if !self.ephemeral.is_empty() {
+            os.write_bytes(4, &self.ephemeral)?;
+        }
+        if !self.static_key.is_empty() {
+            os.write_bytes(5, &self.static_key)?;
+        }
+        if !self.mac.is_empty() {
+            os.write_bytes(6, &self.mac)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Handshake {
+        Handshake::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeEnum<Handshake_Step>>(
+                "step",
+                |m: &Handshake| { &m.step },
+                |m: &mut Handshake| { &mut m.step },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "sender",
+                |m: &Handshake| { &m.sender },
+                |m: &mut Handshake| { &mut m.sender },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "receiver",
+                |m: &Handshake| { &m.receiver },
+                |m: &mut Handshake| { &mut m.receiver },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "ephemeral",
+                |m: &Handshake| { &m.ephemeral },
+                |m: &mut Handshake| { &mut m.ephemeral },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "static_key",
+                |m: &Handshake| { &m.static_key },
+                |m: &mut Handshake| { &mut m.static_key },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "mac",
+                |m: &Handshake| { &m.mac },
+                |m: &mut Handshake| { &mut m.mac },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Handshake>(
+                "Handshake",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Handshake {
+        static instance: ::protobuf::rt::LazyV2<Handshake> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Handshake::new)
+    }
+}
+
+impl ::protobuf::Clear for Handshake {
+    fn clear(&mut self) {
+        self.step = Handshake_Step::INITIATION;
+        self.sender = 0;
+        self.receiver = 0;
+        self.ephemeral.clear();
+        self.static_key.clear();
+        self.mac.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Handshake {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Handshake {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(Clone,PartialEq,Eq,Debug,Hash)]
+pub enum Handshake_Step {
+    INITIATION = 0,
+    RESPONSE = 1,
+}
+
+impl ::protobuf::ProtobufEnum for Handshake_Step {
+    fn value(&self) -> i32 {
+        *self as i32
+    }
+
+    fn from_i32(value: i32) -> ::std::option::Option<Handshake_Step> {
+        match value {
+            0 => ::std::option::Option::Some(Handshake_Step::INITIATION),
+            1 => ::std::option::Option::Some(Handshake_Step::RESPONSE),
+            _ => ::std::option::Option::None
+        }
+    }
+
+    fn values() -> &'static [Self] {
+        static values: &'static [Handshake_Step] = &[
+            Handshake_Step::INITIATION,
+            Handshake_Step::RESPONSE,
+        ];
+        values
+    }
+
+    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            ::protobuf::reflect::EnumDescriptor::new_pb_name::<Handshake_Step>("Handshake.Step", file_descriptor_proto())
+        })
+    }
+}
+
+impl ::std::marker::Copy for Handshake_Step {
+}
+
+impl ::std::default::Default for Handshake_Step {
+    fn default() -> Self {
+        Handshake_Step::INITIATION
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Handshake_Step {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Enum(::protobuf::ProtobufEnum::descriptor(self))
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct FrameShard {
+    // message fields
+    pub frame: ::std::vec::Vec<u8>,
+    pub ttl: u32,
+    pub origin: ::std::string::String,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a FrameShard {
+    fn default() -> &'a FrameShard {
+        <FrameShard as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl FrameShard {
+    pub fn new() -> FrameShard {
+        ::std::default::Default::default()
+    }
+
+    // bytes frame = 1;
+
+
+    pub fn get_frame(&self) -> &[u8] {
+        &self.frame
+    }
+    pub fn clear_frame(&mut self) {
+        self.frame.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_frame(&mut self, v: ::std::vec::Vec<u8>) {
+        self.frame = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_frame(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.frame
+    }
+
+    // Take field
+    pub fn take_frame(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.frame, ::std::vec::Vec::new())
+    }
+
+    // uint32 ttl = 2;
+
+
+    pub fn get_ttl(&self) -> u32 {
+        self.ttl
+    }
+    pub fn clear_ttl(&mut self) {
+        self.ttl = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_ttl(&mut self, v: u32) {
+        self.ttl = v;
+    }
+
+    // string origin = 3;
+
+
+    pub fn get_origin(&self) -> &str {
+        &self.origin
+    }
+    pub fn clear_origin(&mut self) {
+        self.origin.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_origin(&mut self, v: ::std::string::String) {
+        self.origin = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_origin(&mut self) -> &mut ::std::string::String {
+        &mut self.origin
+    }
+
+    // Take field
+    pub fn take_origin(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.origin, ::std::string::String::new())
+    }
+}
+
+impl ::protobuf::Message for FrameShard {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.frame)?;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.ttl = tmp;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.origin)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.frame.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(1, &self.frame);
+        }
+        if self.ttl != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.ttl, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.origin.is_empty() {
+            my_size += ::protobuf::rt::string_size(3, &self.origin);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.frame.is_empty() {
+            os.write_bytes(1, &self.frame)?;
+        }
+        if self.ttl != 0 {
+            os.write_uint32(2, self.ttl)?;
+        }
+        if !self.origin.is_empty() {
+            os.write_string(3, &self.origin)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> FrameShard {
+        FrameShard::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "frame",
+                |m: &FrameShard| { &m.frame },
+                |m: &mut FrameShard| { &mut m.frame },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "ttl",
+                |m: &FrameShard| { &m.ttl },
+                |m: &mut FrameShard| { &mut m.ttl },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "origin",
+                |m: &FrameShard| { &m.origin },
+                |m: &mut FrameShard| { &mut m.origin },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<FrameShard>(
+                "FrameShard",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static FrameShard {
+        static instance: ::protobuf::rt::LazyV2<FrameShard> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(FrameShard::new)
+    }
+}
+
+impl ::protobuf::Clear for FrameShard {
+    fn clear(&mut self) {
+        self.frame.clear();
+        self.ttl = 0;
+        self.origin.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for FrameShard {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for FrameShard {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct LinkState {
+    // message fields
+    pub origin: ::std::string::String,
+    pub seqno: u32,
+    pub age: u32,
+    pub links: ::protobuf::RepeatedField<LinkState_Link>,
+    pub subnets: ::protobuf::RepeatedField<LinkState_Subnet>,
+    pub leaf: bool,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a LinkState {
+    fn default() -> &'a LinkState {
+        <LinkState as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl LinkState {
+    pub fn new() -> LinkState {
+        ::std::default::Default::default()
+    }
+
+    // string origin = 1;
+
+
+    pub fn get_origin(&self) -> &str {
+        &self.origin
+    }
+    pub fn clear_origin(&mut self) {
+        self.origin.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_origin(&mut self, v: ::std::string::String) {
+        self.origin = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_origin(&mut self) -> &mut ::std::string::String {
+        &mut self.origin
+    }
+
+    // Take field
+    pub fn take_origin(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.origin, ::std::string::String::new())
+    }
+
+    // uint32 seqno = 2;
+
+
+    pub fn get_seqno(&self) -> u32 {
+        self.seqno
+    }
+    pub fn clear_seqno(&mut self) {
+        self.seqno = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_seqno(&mut self, v: u32) {
+        self.seqno = v;
+    }
+
+    // uint32 age = 3;
+
+
+    pub fn get_age(&self) -> u32 {
+        self.age
+    }
+    pub fn clear_age(&mut self) {
+        self.age = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_age(&mut self, v: u32) {
+        self.age = v;
+    }
+
+    // repeated .LinkState.Link links = 4;
+
+
+    pub fn get_links(&self) -> &[LinkState_Link] {
+        &self.links
+    }
+    pub fn clear_links(&mut self) {
+        self.links.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_links(&mut self, v: ::protobuf::RepeatedField<LinkState_Link>) {
+        self.links = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_links(&mut self) -> &mut ::protobuf::RepeatedField<LinkState_Link> {
+        &mut self.links
+    }
+
+    // Take field
+    pub fn take_links(&mut self) -> ::protobuf::RepeatedField<LinkState_Link> {
+        ::std::mem::replace(&mut self.links, ::protobuf::RepeatedField::new())
+    }
+
+    // repeated .LinkState.Subnet subnets = 5;
+
+
+    pub fn get_subnets(&self) -> &[LinkState_Subnet] {
+        &self.subnets
+    }
+    pub fn clear_subnets(&mut self) {
+        self.subnets.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_subnets(&mut self, v: ::protobuf::RepeatedField<LinkState_Subnet>) {
+        self.subnets = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_subnets(&mut self) -> &mut ::protobuf::RepeatedField<LinkState_Subnet> {
+        &mut self.subnets
+    }
+
+    // Take field
+    pub fn take_subnets(&mut self) -> ::protobuf::RepeatedField<LinkState_Subnet> {
+        ::std::mem::replace(&mut self.subnets, ::protobuf::RepeatedField::new())
+    }
+
+    // bool leaf = 6;
+
+
+    pub fn get_leaf(&self) -> bool {
+        self.leaf
+    }
+    pub fn clear_leaf(&mut self) {
+        self.leaf = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_leaf(&mut self, v: bool) {
+        self.leaf = v;
+    }
+}
+
+impl ::protobuf::Message for LinkState {
+    fn is_initialized(&self) -> bool {
+        for v in &self.links {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.subnets {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.origin)?;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.seqno = tmp;
+                },
+                3 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.age = tmp;
+                },
+                4 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.links)?;
+                },
+                5 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.subnets)?;
+                },
+                6 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.leaf = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.origin.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.origin);
+        }
+        if self.seqno != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.seqno, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if self.age != 0 {
+            my_size += ::protobuf::rt::value_size(3, self.age, ::protobuf::wire_format::WireTypeVarint);
+        }
+        for value in &self.links {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        for value in &self.subnets {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        if self.leaf != false {
+            my_size += 2;
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.origin.is_empty() {
+            os.write_string(1, &self.origin)?;
+        }
+        if self.seqno != 0 {
+            os.write_uint32(2, self.seqno)?;
+        }
+        if self.age != 0 {
+            os.write_uint32(3, self.age)?;
+        }
+        for v in &self.links {
+            os.write_tag(4, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        for v in &self.subnets {
+            os.write_tag(5, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        if self.leaf != false {
+            os.write_bool(6, self.leaf)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> LinkState {
+        LinkState::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "origin",
+                |m: &LinkState| { &m.origin },
+                |m: &mut LinkState| { &mut m.origin },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "seqno",
+                |m: &LinkState| { &m.seqno },
+                |m: &mut LinkState| { &mut m.seqno },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "age",
+                |m: &LinkState| { &m.age },
+                |m: &mut LinkState| { &mut m.age },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<LinkState_Link>>(
+                "links",
+                |m: &LinkState| { &m.links },
+                |m: &mut LinkState| { &mut m.links },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<LinkState_Subnet>>(
+                "subnets",
+                |m: &LinkState| { &m.subnets },
+                |m: &mut LinkState| { &mut m.subnets },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "leaf",
+                |m: &LinkState| { &m.leaf },
+                |m: &mut LinkState| { &mut m.leaf },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<LinkState>(
+                "LinkState",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static LinkState {
+        static instance: ::protobuf::rt::LazyV2<LinkState> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(LinkState::new)
+    }
+}
+
+impl ::protobuf::Clear for LinkState {
+    fn clear(&mut self) {
+        self.origin.clear();
+        self.seqno = 0;
+        self.age = 0;
+        self.links.clear();
+        self.subnets.clear();
+        self.leaf = false;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for LinkState {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for LinkState {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct LinkState_Link {
+    // message fields
+    pub name: ::std::string::String,
+    pub cost: u32,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a LinkState_Link {
+    fn default() -> &'a LinkState_Link {
+        <LinkState_Link as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl LinkState_Link {
+    pub fn new() -> LinkState_Link {
+        ::std::default::Default::default()
+    }
+
+    // string name = 1;
+
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn clear_name(&mut self) {
+        self.name.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_name(&mut self, v: ::std::string::String) {
+        self.name = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_name(&mut self) -> &mut ::std::string::String {
+        &mut self.name
+    }
+
+    // Take field
+    pub fn take_name(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.name, ::std::string::String::new())
+    }
+
+    // uint32 cost = 2;
+
+
+    pub fn get_cost(&self) -> u32 {
+        self.cost
+    }
+    pub fn clear_cost(&mut self) {
+        self.cost = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_cost(&mut self, v: u32) {
+        self.cost = v;
+    }
+}
+
+impl ::protobuf::Message for LinkState_Link {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.name)?;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.cost = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.name.is_empty() {
+            my_size += ::protobuf::rt::string_size(1, &self.name);
+        }
+        if self.cost != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.cost, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.name.is_empty() {
+            os.write_string(1, &self.name)?;
+        }
+        if self.cost != 0 {
+            os.write_uint32(2, self.cost)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> LinkState_Link {
+        LinkState_Link::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "name",
+                |m: &LinkState_Link| { &m.name },
+                |m: &mut LinkState_Link| { &mut m.name },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "cost",
+                |m: &LinkState_Link| { &m.cost },
+                |m: &mut LinkState_Link| { &mut m.cost },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<LinkState_Link>(
+                "LinkState.Link",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static LinkState_Link {
+        static instance: ::protobuf::rt::LazyV2<LinkState_Link> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(LinkState_Link::new)
+    }
+}
+
+impl ::protobuf::Clear for LinkState_Link {
+    fn clear(&mut self) {
+        self.name.clear();
+        self.cost = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for LinkState_Link {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for LinkState_Link {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct LinkState_Subnet {
+    // message fields
+    pub sub_net: ::std::vec::Vec<u8>,
+    pub net_mask: u32,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a LinkState_Subnet {
+    fn default() -> &'a LinkState_Subnet {
+        <LinkState_Subnet as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl LinkState_Subnet {
+    pub fn new() -> LinkState_Subnet {
+        ::std::default::Default::default()
+    }
+
+    // bytes sub_net = 1;
+
+
+    pub fn get_sub_net(&self) -> &[u8] {
+        &self.sub_net
+    }
+    pub fn clear_sub_net(&mut self) {
+        self.sub_net.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_sub_net(&mut self, v: ::std::vec::Vec<u8>) {
+        self.sub_net = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_sub_net(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.sub_net
+    }
+
+    // Take field
+    pub fn take_sub_net(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.sub_net, ::std::vec::Vec::new())
+    }
+
+    // uint32 net_mask = 2;
+
+
+    pub fn get_net_mask(&self) -> u32 {
+        self.net_mask
+    }
+    pub fn clear_net_mask(&mut self) {
+        self.net_mask = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_net_mask(&mut self, v: u32) {
+        self.net_mask = v;
+    }
+}
+
+impl ::protobuf::Message for LinkState_Subnet {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.sub_net)?;
+                },
+                2 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.net_mask = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.sub_net.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(1, &self.sub_net);
+        }
+        if self.net_mask != 0 {
+            my_size += ::protobuf::rt::value_size(2, self.net_mask, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.sub_net.is_empty() {
+            os.write_bytes(1, &self.sub_net)?;
+        }
+        if self.net_mask != 0 {
+            os.write_uint32(2, self.net_mask)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> LinkState_Subnet {
+        LinkState_Subnet::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "sub_net",
+                |m: &LinkState_Subnet| { &m.sub_net },
+                |m: &mut LinkState_Subnet| { &mut m.sub_net },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "net_mask",
+                |m: &LinkState_Subnet| { &m.net_mask },
+                |m: &mut LinkState_Subnet| { &mut m.net_mask },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<LinkState_Subnet>(
+                "LinkState.Subnet",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static LinkState_Subnet {
+        static instance: ::protobuf::rt::LazyV2<LinkState_Subnet> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(LinkState_Subnet::new)
+    }
+}
+
+impl ::protobuf::Clear for LinkState_Subnet {
+    fn clear(&mut self) {
+        self.sub_net.clear();
+        self.net_mask = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for LinkState_Subnet {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for LinkState_Subnet {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Endpoint {
+    // message fields
+    pub step: Endpoint_Step,
+    pub name: ::std::string::String,
+    pub ip: ::std::vec::Vec<u8>,
+    pub port: u32,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Endpoint {
+    fn default() -> &'a Endpoint {
+        <Endpoint as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Endpoint {
+    pub fn new() -> Endpoint {
+        ::std::default::Default::default()
+    }
+
+    // .Endpoint.Step step = 1;
+
+
+    pub fn get_step(&self) -> Endpoint_Step {
+        self.step
+    }
+    pub fn clear_step(&mut self) {
+        self.step = Endpoint_Step::REQUEST;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_step(&mut self, v: Endpoint_Step) {
+        self.step = v;
+    }
+
+    // string name = 2;
+
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn clear_name(&mut self) {
+        self.name.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_name(&mut self, v: ::std::string::String) {
+        self.name = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_name(&mut self) -> &mut ::std::string::String {
+        &mut self.name
+    }
+
+    // Take field
+    pub fn take_name(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.name, ::std::string::String::new())
+    }
+
+    // bytes ip = 3;
+
+
+    pub fn get_ip(&self) -> &[u8] {
+        &self.ip
+    }
+    pub fn clear_ip(&mut self) {
+        self.ip.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_ip(&mut self, v: ::std::vec::Vec<u8>) {
+        self.ip = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_ip(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.ip
+    }
+
+    // Take field
+    pub fn take_ip(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.ip, ::std::vec::Vec::new())
+    }
+
+    // uint32 port = 4;
+
+
+    pub fn get_port(&self) -> u32 {
+        self.port
+    }
+    pub fn clear_port(&mut self) {
+        self.port = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_port(&mut self, v: u32) {
+        self.port = v;
+    }
+}
+
+impl ::protobuf::Message for Endpoint {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_proto3_enum_with_unknown_fields_into(wire_type, is, &mut self.step, 1, &mut self.unknown_fields)?
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.name)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.ip)?;
+                },
+                4 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.port = tmp;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.step != Endpoint_Step::REQUEST {
+            my_size += ::protobuf::rt::enum_size(1, self.step);
+        }
+        if !self.name.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.name);
+        }
+        if !self.ip.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(3, &self.ip);
+        }
+        if self.port != 0 {
+            my_size += ::protobuf::rt::value_size(4, self.port, ::protobuf::wire_format::WireTypeVarint);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.step != Endpoint_Step::REQUEST {
+            os.write_enum(1, ::protobuf::ProtobufEnum::value(&self.step))?;
+        }
+        if !self.name.is_empty() {
+            os.write_string(2, &self.name)?;
+        }
+        if !self.ip.is_empty() {
+            os.write_bytes(3, &self.ip)?;
+        }
+        if self.port != 0 {
+            os.write_uint32(4, self.port)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Endpoint {
+        Endpoint::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeEnum<Endpoint_Step>>(
+                "step",
+                |m: &Endpoint| { &m.step },
+                |m: &mut Endpoint| { &mut m.step },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "name",
+                |m: &Endpoint| { &m.name },
+                |m: &mut Endpoint| { &mut m.name },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "ip",
+                |m: &Endpoint| { &m.ip },
+                |m: &mut Endpoint| { &mut m.ip },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "port",
+                |m: &Endpoint| { &m.port },
+                |m: &mut Endpoint| { &mut m.port },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Endpoint>(
+                "Endpoint",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Endpoint {
+        static instance: ::protobuf::rt::LazyV2<Endpoint> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Endpoint::new)
+    }
+}
+
+impl ::protobuf::Clear for Endpoint {
+    fn clear(&mut self) {
+        self.step = Endpoint_Step::REQUEST;
+        self.name.clear();
+        self.ip.clear();
+        self.port = 0;
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Endpoint {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Endpoint {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(Clone,PartialEq,Eq,Debug,Hash)]
+pub enum Endpoint_Step {
+    REQUEST = 0,
+    PUNCH = 1,
+}
+
+impl ::protobuf::ProtobufEnum for Endpoint_Step {
+    fn value(&self) -> i32 {
+        *self as i32
+    }
+
+    fn from_i32(value: i32) -> ::std::option::Option<Endpoint_Step> {
+        match value {
+            0 => ::std::option::Option::Some(Endpoint_Step::REQUEST),
+            1 => ::std::option::Option::Some(Endpoint_Step::PUNCH),
+            _ => ::std::option::Option::None
+        }
+    }
+
+    fn values() -> &'static [Self] {
+        static values: &'static [Endpoint_Step] = &[
+            Endpoint_Step::REQUEST,
+            Endpoint_Step::PUNCH,
+        ];
+        values
+    }
+
+    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            ::protobuf::reflect::EnumDescriptor::new_pb_name::<Endpoint_Step>("Endpoint.Step", file_descriptor_proto())
+        })
+    }
+}
+
+impl ::std::marker::Copy for Endpoint_Step {
+}
+
+impl ::std::default::Default for Endpoint_Step {
+    fn default() -> Self {
+        Endpoint_Step::REQUEST
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Endpoint_Step {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Enum(::protobuf::ProtobufEnum::descriptor(self))
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct Payload {
+    // message oneof groups
+    pub payload: ::std::option::Option<Payload_oneof_payload>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a Payload {
+    fn default() -> &'a Payload {
+        <Payload as ::protobuf::Message>::default_instance()
+    }
+}
+
+#[derive(Clone,PartialEq,Debug)]
+pub enum Payload_oneof_payload {
+    package(PackageShard),
+    add_node(Node),
+    del_node(Node),
+    ping(PingPong),
+    handshake(Handshake),
+    link_state(LinkState),
+    frame(FrameShard),
+    endpoint(Endpoint),
+}
+
+impl Payload {
+    pub fn new() -> Payload {
+        ::std::default::Default::default()
+    }
+
+    // .PackageShard package = 1;
+
+
+    pub fn get_package(&self) -> &PackageShard {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::package(ref v)) => v,
+            _ => <PackageShard as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_package(&mut self) {
+        self.payload = ::std::option::Option::None;
+    }
+
+    pub fn has_package(&self) -> bool {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::package(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_package(&mut self, v: PackageShard) {
+        self.payload = ::std::option::Option::Some(Payload_oneof_payload::package(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_package(&mut self) -> &mut PackageShard {
+        if let ::std::option::Option::Some(Payload_oneof_payload::package(_)) = self.payload {
+        } else {
+            self.payload = ::std::option::Option::Some(Payload_oneof_payload::package(PackageShard::new()));
+        }
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::package(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_package(&mut self) -> PackageShard {
+        if self.has_package() {
+            match self.payload.take() {
+                ::std::option::Option::Some(Payload_oneof_payload::package(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            PackageShard::new()
+        }
+    }
+
+    // .Node add_node = 3;
+
+
+    pub fn get_add_node(&self) -> &Node {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::add_node(ref v)) => v,
+            _ => <Node as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_add_node(&mut self) {
+        self.payload = ::std::option::Option::None;
+    }
+
+    pub fn has_add_node(&self) -> bool {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::add_node(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_add_node(&mut self, v: Node) {
+        self.payload = ::std::option::Option::Some(Payload_oneof_payload::add_node(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_add_node(&mut self) -> &mut Node {
+        if let ::std::option::Option::Some(Payload_oneof_payload::add_node(_)) = self.payload {
+        } else {
+            self.payload = ::std::option::Option::Some(Payload_oneof_payload::add_node(Node::new()));
+        }
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::add_node(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_add_node(&mut self) -> Node {
+        if self.has_add_node() {
+            match self.payload.take() {
+                ::std::option::Option::Some(Payload_oneof_payload::add_node(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            Node::new()
+        }
+    }
+
+    // .Node del_node = 4;
+
+
+    pub fn get_del_node(&self) -> &Node {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::del_node(ref v)) => v,
+            _ => <Node as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_del_node(&mut self) {
+        self.payload = ::std::option::Option::None;
+    }
+
+    pub fn has_del_node(&self) -> bool {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::del_node(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_del_node(&mut self, v: Node) {
+        self.payload = ::std::option::Option::Some(Payload_oneof_payload::del_node(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_del_node(&mut self) -> &mut Node {
+        if let ::std::option::Option::Some(Payload_oneof_payload::del_node(_)) = self.payload {
+        } else {
+            self.payload = ::std::option::Option::Some(Payload_oneof_payload::del_node(Node::new()));
+        }
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::del_node(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_del_node(&mut self) -> Node {
+        if self.has_del_node() {
+            match self.payload.take() {
+                ::std::option::Option::Some(Payload_oneof_payload::del_node(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            Node::new()
+        }
+    }
+
+    // .PingPong ping = 7;
+
+
+    pub fn get_ping(&self) -> &PingPong {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::ping(ref v)) => v,
+            _ => <PingPong as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_ping(&mut self) {
+        self.payload = ::std::option::Option::None;
+    }
+
+    pub fn has_ping(&self) -> bool {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::ping(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_ping(&mut self, v: PingPong) {
+        self.payload = ::std::option::Option::Some(Payload_oneof_payload::ping(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_ping(&mut self) -> &mut PingPong {
+        if let ::std::option::Option::Some(Payload_oneof_payload::ping(_)) = self.payload {
+        } else {
+            self.payload = ::std::option::Option::Some(Payload_oneof_payload::ping(PingPong::new()));
+        }
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::ping(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_ping(&mut self) -> PingPong {
+        if self.has_ping() {
+            match self.payload.take() {
+                ::std::option::Option::Some(Payload_oneof_payload::ping(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            PingPong::new()
+        }
+    }
+
+    // .Handshake handshake = 8;
+
+
+    pub fn get_handshake(&self) -> &Handshake {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::handshake(ref v)) => v,
+            _ => <Handshake as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_handshake(&mut self) {
+        self.payload = ::std::option::Option::None;
+    }
+
+    pub fn has_handshake(&self) -> bool {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::handshake(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_handshake(&mut self, v: Handshake) {
+        self.payload = ::std::option::Option::Some(Payload_oneof_payload::handshake(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_handshake(&mut self) -> &mut Handshake {
+        if let ::std::option::Option::Some(Payload_oneof_payload::handshake(_)) = self.payload {
+        } else {
+            self.payload = ::std::option::Option::Some(Payload_oneof_payload::handshake(Handshake::new()));
+        }
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::handshake(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_handshake(&mut self) -> Handshake {
+        if self.has_handshake() {
+            match self.payload.take() {
+                ::std::option::Option::Some(Payload_oneof_payload::handshake(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            Handshake::new()
+        }
+    }
+
+    // .LinkState link_state = 9;
+
+
+    pub fn get_link_state(&self) -> &LinkState {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::link_state(ref v)) => v,
+            _ => <LinkState as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_link_state(&mut self) {
+        self.payload = ::std::option::Option::None;
+    }
+
+    pub fn has_link_state(&self) -> bool {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::link_state(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_link_state(&mut self, v: LinkState) {
+        self.payload = ::std::option::Option::Some(Payload_oneof_payload::link_state(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_link_state(&mut self) -> &mut LinkState {
+        if let ::std::option::Option::Some(Payload_oneof_payload::link_state(_)) = self.payload {
+        } else {
+            self.payload = ::std::option::Option::Some(Payload_oneof_payload::link_state(LinkState::new()));
+        }
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::link_state(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_link_state(&mut self) -> LinkState {
+        if self.has_link_state() {
+            match self.payload.take() {
+                ::std::option::Option::Some(Payload_oneof_payload::link_state(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            LinkState::new()
+        }
+    }
+
+    // .FrameShard frame = 10;
+
+
+    pub fn get_frame(&self) -> &FrameShard {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::frame(ref v)) => v,
+            _ => <FrameShard as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_frame(&mut self) {
+        self.payload = ::std::option::Option::None;
+    }
+
+    pub fn has_frame(&self) -> bool {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::frame(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_frame(&mut self, v: FrameShard) {
+        self.payload = ::std::option::Option::Some(Payload_oneof_payload::frame(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_frame(&mut self) -> &mut FrameShard {
+        if let ::std::option::Option::Some(Payload_oneof_payload::frame(_)) = self.payload {
+        } else {
+            self.payload = ::std::option::Option::Some(Payload_oneof_payload::frame(FrameShard::new()));
+        }
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::frame(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_frame(&mut self) -> FrameShard {
+        if self.has_frame() {
+            match self.payload.take() {
+                ::std::option::Option::Some(Payload_oneof_payload::frame(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            FrameShard::new()
+        }
+    }
+
+    // .Endpoint endpoint = 11;
+
+
+    pub fn get_endpoint(&self) -> &Endpoint {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::endpoint(ref v)) => v,
+            _ => <Endpoint as ::protobuf::Message>::default_instance(),
+        }
+    }
+    pub fn clear_endpoint(&mut self) {
+        self.payload = ::std::option::Option::None;
+    }
+
+    pub fn has_endpoint(&self) -> bool {
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::endpoint(..)) => true,
+            _ => false,
+        }
+    }
+
+    // Param is passed by value, moved
+    pub fn set_endpoint(&mut self, v: Endpoint) {
+        self.payload = ::std::option::Option::Some(Payload_oneof_payload::endpoint(v))
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_endpoint(&mut self) -> &mut Endpoint {
+        if let ::std::option::Option::Some(Payload_oneof_payload::endpoint(_)) = self.payload {
+        } else {
+            self.payload = ::std::option::Option::Some(Payload_oneof_payload::endpoint(Endpoint::new()));
+        }
+        match self.payload {
+            ::std::option::Option::Some(Payload_oneof_payload::endpoint(ref mut v)) => v,
+            _ => panic!(),
+        }
+    }
+
+    // Take field
+    pub fn take_endpoint(&mut self) -> Endpoint {
+        if self.has_endpoint() {
+            match self.payload.take() {
+                ::std::option::Option::Some(Payload_oneof_payload::endpoint(v)) => v,
+                _ => panic!(),
+            }
+        } else {
+            Endpoint::new()
+        }
+    }
+}
+
+impl ::protobuf::Message for Payload {
+    fn is_initialized(&self) -> bool {
+        if let Some(Payload_oneof_payload::package(ref v)) = self.payload {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(Payload_oneof_payload::add_node(ref v)) = self.payload {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(Payload_oneof_payload::del_node(ref v)) = self.payload {
+            if !v.is_initialized() {
+                return false;
+            }
         }
         if let Some(Payload_oneof_payload::ping(ref v)) = self.payload {
             if !v.is_initialized() {
                 return false;
             }
         }
+        if let Some(Payload_oneof_payload::handshake(ref v)) = self.payload {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(Payload_oneof_payload::link_state(ref v)) = self.payload {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(Payload_oneof_payload::frame(ref v)) = self.payload {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
+        if let Some(Payload_oneof_payload::endpoint(ref v)) = self.payload {
+            if !v.is_initialized() {
+                return false;
+            }
+        }
         true
     }
 
-    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream) -> ::protobuf::ProtobufResult<()> {
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
         while !is.eof()? {
             let (field_number, wire_type) = is.read_tag_unpack()?;
             match field_number {
@@ -1030,6 +3059,30 @@ impl ::protobuf::Message for Payload {
                     }
                     self.payload = ::std::option::Option::Some(Payload_oneof_payload::ping(is.read_message()?));
                 },
+                8 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.payload = ::std::option::Option::Some(Payload_oneof_payload::handshake(is.read_message()?));
+                },
+                9 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.payload = ::std::option::Option::Some(Payload_oneof_payload::link_state(is.read_message()?));
+                },
+                10 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.payload = ::std::option::Option::Some(Payload_oneof_payload::frame(is.read_message()?));
+                },
+                11 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeLengthDelimited {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    self.payload = ::std::option::Option::Some(Payload_oneof_payload::endpoint(is.read_message()?));
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -1060,6 +3113,22 @@ impl ::protobuf::Message for Payload {
                     let len = v.compute_size();
                     my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
                 },
+                &Payload_oneof_payload::handshake(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &Payload_oneof_payload::link_state(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &Payload_oneof_payload::frame(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
+                &Payload_oneof_payload::endpoint(ref v) => {
+                    let len = v.compute_size();
+                    my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+                },
             };
         }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
@@ -1067,7 +3136,7 @@ impl ::protobuf::Message for Payload {
         my_size
     }
 
-    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream) -> ::protobuf::ProtobufResult<()> {
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
         if let ::std::option::Option::Some(ref v) = self.payload {
             match v {
                 &Payload_oneof_payload::package(ref v) => {
@@ -1090,6 +3159,26 @@ impl ::protobuf::Message for Payload {
                     os.write_raw_varint32(v.get_cached_size())?;
                     v.write_to_with_cached_sizes(os)?;
                 },
+                &Payload_oneof_payload::handshake(ref v) => {
+                    os.write_tag(8, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Payload_oneof_payload::link_state(ref v) => {
+                    os.write_tag(9, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Payload_oneof_payload::frame(ref v) => {
+                    os.write_tag(10, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
+                &Payload_oneof_payload::endpoint(ref v) => {
+                    os.write_tag(11, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+                    os.write_raw_varint32(v.get_cached_size())?;
+                    v.write_to_with_cached_sizes(os)?;
+                },
             };
         }
         os.write_unknown_fields(self.get_unknown_fields())?;
@@ -1108,13 +3197,13 @@ impl ::protobuf::Message for Payload {
         &mut self.unknown_fields
     }
 
-    fn as_any(&self) -> &::std::any::Any {
-        self as &::std::any::Any
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
     }
-    fn as_any_mut(&mut self) -> &mut ::std::any::Any {
-        self as &mut ::std::any::Any
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
     }
-    fn into_any(self: Box<Self>) -> ::std::boxed::Box<::std::any::Any> {
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
         self
     }
 
@@ -1127,55 +3216,69 @@ impl ::protobuf::Message for Payload {
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
-        static mut descriptor: ::protobuf::lazy::Lazy<::protobuf::reflect::MessageDescriptor> = ::protobuf::lazy::Lazy {
-            lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const ::protobuf::reflect::MessageDescriptor,
-        };
-        unsafe {
-            descriptor.get(|| {
-                let mut fields = ::std::vec::Vec::new();
-                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, PackageShard>(
-                    "package",
-                    Payload::has_package,
-                    Payload::get_package,
-                ));
-                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, Node>(
-                    "add_node",
-                    Payload::has_add_node,
-                    Payload::get_add_node,
-                ));
-                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, Node>(
-                    "del_node",
-                    Payload::has_del_node,
-                    Payload::get_del_node,
-                ));
-                fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, PingPong>(
-                    "ping",
-                    Payload::has_ping,
-                    Payload::get_ping,
-                ));
-                ::protobuf::reflect::MessageDescriptor::new::<Payload>(
-                    "Payload",
-                    fields,
-                    file_descriptor_proto()
-                )
-            })
-        }
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, PackageShard>(
+                "package",
+                Payload::has_package,
+                Payload::get_package,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, Node>(
+                "add_node",
+                Payload::has_add_node,
+                Payload::get_add_node,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, Node>(
+                "del_node",
+                Payload::has_del_node,
+                Payload::get_del_node,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, PingPong>(
+                "ping",
+                Payload::has_ping,
+                Payload::get_ping,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, Handshake>(
+                "handshake",
+                Payload::has_handshake,
+                Payload::get_handshake,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, LinkState>(
+                "link_state",
+                Payload::has_link_state,
+                Payload::get_link_state,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, FrameShard>(
+                "frame",
+                Payload::has_frame,
+                Payload::get_frame,
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_message_accessor::<_, Endpoint>(
+                "endpoint",
+                Payload::has_endpoint,
+                Payload::get_endpoint,
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Payload>(
+                "Payload",
+                fields,
+                file_descriptor_proto()
+            )
+        })
     }
 
     fn default_instance() -> &'static Payload {
-        static mut instance: ::protobuf::lazy::Lazy<Payload> = ::protobuf::lazy::Lazy {
-            lock: ::protobuf::lazy::ONCE_INIT,
-            ptr: 0 as *const Payload,
-        };
-        unsafe {
-            instance.get(Payload::new)
-        }
+        static instance: ::protobuf::rt::LazyV2<Payload> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Payload::new)
     }
 }
 
 impl ::protobuf::Clear for Payload {
     fn clear(&mut self) {
+        self.payload = ::std::option::Option::None;
+        self.payload = ::std::option::Option::None;
+        self.payload = ::std::option::Option::None;
+        self.payload = ::std::option::Option::None;
         self.payload = ::std::option::Option::None;
         self.payload = ::std::option::Option::None;
         self.payload = ::std::option::Option::None;
@@ -1185,44 +3288,70 @@ impl ::protobuf::Clear for Payload {
 }
 
 impl ::std::fmt::Debug for Payload {
-    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
 impl ::protobuf::reflect::ProtobufValue for Payload {
-    fn as_ref(&self) -> ::protobuf::reflect::ProtobufValueRef {
-        ::protobuf::reflect::ProtobufValueRef::Message(self)
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
     }
 }
 
 static file_descriptor_proto_data: &'static [u8] = b"\
-    \n\x0ftransport.proto\x12\0\"2\n\x0cPackageShard\x12\r\n\x03ttl\x18\x01\
-    \x20\x01(\rB\0\x12\x11\n\x07package\x18\x02\x20\x01(\x0cB\0:\0\"r\n\x04N\
-    ode\x12\x11\n\x07sub_net\x18\x01\x20\x01(\x0cB\0\x12\x12\n\x08net_mask\
-    \x18\x02\x20\x01(\rB\0\x12\x0e\n\x04name\x18\x05\x20\x01(\tB\0\x12\x0e\n\
-    \x04jump\x18\x06\x20\x01(\x05B\0\x12\x11\n\x07real_ip\x18\x07\x20\x01(\
-    \x0cB\0\x12\x0e\n\x04port\x18\x08\x20\x01(\x05B\0:\0\"\x1c\n\x08PingPong\
-    \x12\x0e\n\x04name\x18\x01\x20\x01(\tB\0:\0\"\x91\x01\n\x07Payload\x12\"\
-    \n\x07package\x18\x01\x20\x01(\x0b2\r.PackageShardH\0B\0\x12\x1b\n\x08ad\
-    d_node\x18\x03\x20\x01(\x0b2\x05.NodeH\0B\0\x12\x1b\n\x08del_node\x18\
-    \x04\x20\x01(\x0b2\x05.NodeH\0B\0\x12\x1b\n\x04ping\x18\x07\x20\x01(\x0b\
-    2\t.PingPongH\0B\0B\t\n\x07payload:\0B\0b\x06proto3\
+    \n\x0ftransport.proto\"@\n\x0cPackageShard\x12\x12\n\x03ttl\x18\x01\x20\
+    \x01(\rR\x03ttlB\0\x12\x1a\n\x07package\x18\x02\x20\x01(\x0cR\x07package\
+    B\0:\0\"\xcf\x01\n\x04Node\x12\x19\n\x07sub_net\x18\x01\x20\x01(\x0cR\
+    \x06subNetB\0\x12\x1b\n\x08net_mask\x18\x02\x20\x01(\rR\x07netMaskB\0\
+    \x12\x14\n\x04name\x18\x05\x20\x01(\tR\x04nameB\0\x12\x14\n\x04jump\x18\
+    \x06\x20\x01(\x05R\x04jumpB\0\x12\x19\n\x07real_ip\x18\x07\x20\x01(\x0cR\
+    \x06realIpB\0\x12\x14\n\x04port\x18\x08\x20\x01(\x05R\x04portB\0\x12\x18\
+    \n\x06metric\x18\t\x20\x01(\rR\x06metricB\0\x12\x16\n\x05seqno\x18\n\x20\
+    \x01(\rR\x05seqnoB\0:\0\"X\n\x08PingPong\x12\x14\n\x04name\x18\x01\x20\
+    \x01(\tR\x04nameB\0\x12\x1c\n\x08sequence\x18\x02\x20\x01(\x04R\x08seque\
+    nceB\0\x12\x16\n\x05reply\x18\x03\x20\x01(\x08R\x05replyB\0:\0\"\xe9\x01\
+    \n\tHandshake\x12%\n\x04step\x18\x01\x20\x01(\x0e2\x0f.Handshake.StepR\
+    \x04stepB\0\x12\x18\n\x06sender\x18\x02\x20\x01(\rR\x06senderB\0\x12\x1c\
+    \n\x08receiver\x18\x03\x20\x01(\rR\x08receiverB\0\x12\x1e\n\tephemeral\
+    \x18\x04\x20\x01(\x0cR\tephemeralB\0\x12\x1f\n\nstatic_key\x18\x05\x20\
+    \x01(\x0cR\tstaticKeyB\0\x12\x12\n\x03mac\x18\x06\x20\x01(\x0cR\x03macB\
+    \0\"&\n\x04Step\x12\x0e\n\nINITIATION\x10\0\x12\x0c\n\x08RESPONSE\x10\
+    \x01\x1a\0:\0\"T\n\nFrameShard\x12\x16\n\x05frame\x18\x01\x20\x01(\x0cR\
+    \x05frameB\0\x12\x12\n\x03ttl\x18\x02\x20\x01(\rR\x03ttlB\0\x12\x18\n\
+    \x06origin\x18\x03\x20\x01(\tR\x06originB\0:\0\"\xbb\x02\n\tLinkState\
+    \x12\x18\n\x06origin\x18\x01\x20\x01(\tR\x06originB\0\x12\x16\n\x05seqno\
+    \x18\x02\x20\x01(\rR\x05seqnoB\0\x12\x12\n\x03age\x18\x03\x20\x01(\rR\
+    \x03ageB\0\x12'\n\x05links\x18\x04\x20\x03(\x0b2\x0f.LinkState.LinkR\x05\
+    linksB\0\x12-\n\x07subnets\x18\x05\x20\x03(\x0b2\x11.LinkState.SubnetR\
+    \x07subnetsB\0\x12\x14\n\x04leaf\x18\x06\x20\x01(\x08R\x04leafB\0\x1a4\n\
+    \x04Link\x12\x14\n\x04name\x18\x01\x20\x01(\tR\x04nameB\0\x12\x14\n\x04c\
+    ost\x18\x02\x20\x01(\rR\x04costB\0:\0\x1aB\n\x06Subnet\x12\x19\n\x07sub_\
+    net\x18\x01\x20\x01(\x0cR\x06subNetB\0\x12\x1b\n\x08net_mask\x18\x02\x20\
+    \x01(\rR\x07netMaskB\0:\0:\0\"\x92\x01\n\x08Endpoint\x12$\n\x04step\x18\
+    \x01\x20\x01(\x0e2\x0e.Endpoint.StepR\x04stepB\0\x12\x14\n\x04name\x18\
+    \x02\x20\x01(\tR\x04nameB\0\x12\x10\n\x02ip\x18\x03\x20\x01(\x0cR\x02ipB\
+    \0\x12\x14\n\x04port\x18\x04\x20\x01(\rR\x04portB\0\"\x20\n\x04Step\x12\
+    \x0b\n\x07REQUEST\x10\0\x12\t\n\x05PUNCH\x10\x01\x1a\0:\0\"\xe1\x02\n\
+    \x07Payload\x12+\n\x07package\x18\x01\x20\x01(\x0b2\r.PackageShardH\0R\
+    \x07packageB\0\x12$\n\x08add_node\x18\x03\x20\x01(\x0b2\x05.NodeH\0R\x07\
+    addNodeB\0\x12$\n\x08del_node\x18\x04\x20\x01(\x0b2\x05.NodeH\0R\x07delN\
+    odeB\0\x12!\n\x04ping\x18\x07\x20\x01(\x0b2\t.PingPongH\0R\x04pingB\0\
+    \x12,\n\thandshake\x18\x08\x20\x01(\x0b2\n.HandshakeH\0R\thandshakeB\0\
+    \x12-\n\nlink_state\x18\t\x20\x01(\x0b2\n.LinkStateH\0R\tlinkStateB\0\
+    \x12%\n\x05frame\x18\n\x20\x01(\x0b2\x0b.FrameShardH\0R\x05frameB\0\x12)\
+    \n\x08endpoint\x18\x0b\x20\x01(\x0b2\t.EndpointH\0R\x08endpointB\0B\t\n\
+    \x07payload:\0B\0b\x06proto3\
 ";
 
-static mut file_descriptor_proto_lazy: ::protobuf::lazy::Lazy<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::lazy::Lazy {
-    lock: ::protobuf::lazy::ONCE_INIT,
-    ptr: 0 as *const ::protobuf::descriptor::FileDescriptorProto,
-};
+static file_descriptor_proto_lazy: ::protobuf::rt::LazyV2<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::rt::LazyV2::INIT;
 
 fn parse_descriptor_proto() -> ::protobuf::descriptor::FileDescriptorProto {
-    ::protobuf::parse_from_bytes(file_descriptor_proto_data).unwrap()
+    ::protobuf::Message::parse_from_bytes(file_descriptor_proto_data).unwrap()
 }
 
 pub fn file_descriptor_proto() -> &'static ::protobuf::descriptor::FileDescriptorProto {
-    unsafe {
-        file_descriptor_proto_lazy.get(|| {
-            parse_descriptor_proto()
-        })
-    }
+    file_descriptor_proto_lazy.get(|| {
+        parse_descriptor_proto()
+    })
 }
@@ -2,9 +2,14 @@ pub mod tuntap_mio;
 pub mod tuntap_tokio;
 
 use crate::config::Config;
+use crate::interface::tuntap_mio::TunTap as TunTapMio;
 use crate::interface::tuntap_tokio::TunTap;
+use crate::internal::buffer::{self, Buffer};
 use crate::internal::error::Error;
-use crate::internal::package::{Buffer, Package};
+use crate::internal::frame::{self, Frame};
+use crate::internal::message::Message;
+use crate::internal::package::Package;
+use crate::internal::queue;
 use crate::utils::*;
 use log::*;
 use serde::{Deserialize, Serialize};
@@ -12,12 +17,10 @@ use std::collections::linked_list::LinkedList;
 use std::ffi::CString;
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::prelude::stream::Stream;
-use tokio::prelude::{Async, Future};
-use tokio::sync::mpsc;
+use tokio::prelude::{task, Async, Future};
 
 lazy_static! {
     static ref TUN_PATH: CString = CString::new("/dev/net/tun").unwrap();
-    static ref TAP_PATH: CString = CString::new("/dev/tap0").unwrap();
 }
 
 #[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Copy, Clone)]
@@ -25,29 +28,29 @@ pub enum Type {
     /// Tun device read and write IP package
     #[serde(rename = "tun")]
     Tun,
-    /// Not unimplemented!
-    ///
-    /// Tap device read and write ethernet frame
+    /// Tap device read and write ethernet frame,
+    /// the whole mesh is bridged as one ethernet
     #[serde(rename = "tap")]
     Tap,
 }
 
 pub struct Device {
     interface: TunTap,
-    receiver_net: mpsc::UnboundedReceiver<Package>,
-    sender_net: mpsc::UnboundedSender<Package>,
-    buffer: LinkedList<Vec<u8>>,
+    /// a tun device read `InterfaceRead` and write `InterfaceWrite`, a tap
+    /// device read `FrameRead` and write `FrameWrite`
+    device_type: Type,
+    receiver_net: queue::Receiver<Message>,
+    sender_net: queue::Sender<Message>,
+    /// packages wait for the device, at most `queue::CAPACITY`
+    buffer: LinkedList<Buffer>,
 }
 
 impl Device {
-    pub fn new(rx: mpsc::UnboundedReceiver<Package>, tx: mpsc::UnboundedSender<Package>) -> Self {
-        let c = Config::get();
-        let interface = TunTap::new(&c.device_name, c.device_type);
-
-        run_command(&c.get_env(), &c.ifup);
-
+    /// poll one queue of the device, opened by `Device::open`
+    pub fn new(rx: queue::Receiver<Message>, tx: queue::Sender<Message>, queue: TunTapMio) -> Self {
         Device {
-            interface,
+            interface: TunTap::from_queue(queue),
+            device_type: Config::get().device_type,
             receiver_net: rx,
             sender_net: tx,
             buffer: LinkedList::new(),
@@ -55,44 +58,91 @@ impl Device {
     }
 }
 
+impl Device {
+    /// open the queues of the device, one for every worker, and run `ifup`
+    /// and `exit_up` of the config
+    pub fn open() -> Vec<TunTapMio> {
+        let c = Config::get();
+        let queues = TunTapMio::queues(&c.device_name, c.device_type, c.workers());
+
+        run_command(&c.get_env(), &c.ifup);
+        if c.exit_node && !c.exit_up.is_empty() {
+            run_command(&c.get_env(), &c.exit_up);
+        }
+        queues
+    }
+
+    /// run `exit_down` and `ifdown` of the config, call it before exit
+    pub fn down() {
+        let c = Config::get();
+        if c.exit_node && !c.exit_down.is_empty() {
+            run_command(&c.get_env(), &c.exit_down);
+        }
+        run_command(&c.get_env(), &c.ifdown);
+    }
+}
+
 impl Future for Device {
     type Item = ();
     type Error = Error;
 
     fn poll(&mut self) -> Result<Async<Self::Item>, Self::Error> {
-        loop {
-            match self.receiver_net.poll()? {
-                Async::Ready(Some(p)) => {
+        // stop reading when full, so the queue before it fill and drop
+        while self.buffer.len() < queue::CAPACITY {
+            match self.receiver_net.poll() {
+                Ok(Async::Ready(Some(Message::InterfaceWrite(p)))) => {
                     self.buffer.push_back(p.raw_package);
                 }
-                Async::Ready(None) => panic!(),
-                Async::NotReady => break,
+                Ok(Async::Ready(Some(Message::FrameWrite(f)))) => {
+                    self.buffer.push_back(f.raw_frame);
+                }
+                Ok(Async::Ready(Some(m))) => {
+                    warn!("device can not write {:?}", m);
+                }
+                Ok(Async::NotReady) => break,
+                Ok(Async::Ready(None)) | Err(()) => {
+                    warn!("network is gone, stop the device");
+                    return Ok(Async::Ready(()));
+                }
             }
         }
 
         loop {
-            let mut buffer = Buffer::get();
-            match self.interface.poll_read(buffer.as_mut_slice())? {
+            // a frame has the ethernet header before the IP package
+            let mut buffer = match self.device_type {
+                Type::Tun => Buffer::get(),
+                Type::Tap => Buffer::with_len(buffer::MTU + frame::HEADER_LEN),
+            };
+            match self.interface.poll_read(&mut buffer)? {
                 Async::Ready(nbytes) => {
-                    Buffer::set_len(buffer.as_mut(), nbytes);
-                    let package = Package::from_buffer(buffer);
-                    self.sender_net.try_send(package).unwrap();
+                    buffer.set_len(nbytes);
+                    let m = match self.device_type {
+                        Type::Tun => Message::InterfaceRead(Package::from_buffer(buffer)),
+                        Type::Tap => Message::FrameRead(Frame::from_buffer(buffer)),
+                    };
+                    self.sender_net.send(m);
                 }
                 Async::NotReady => break,
             }
         }
 
+        let full = self.buffer.len() >= queue::CAPACITY;
         while let Some(buff) = self.buffer.pop_front() {
             match self.interface.poll_write(&buff)? {
                 Async::Ready(nbytes) => {
                     info!("write {} bytes to interface", nbytes);
                 }
                 Async::NotReady => {
-                    self.buffer.push_back(buff);
+                    // keep the order of the packages
+                    self.buffer.push_front(buff);
                     break;
                 }
             }
         }
+        // the queue is not read when full, poll it again
+        if full && self.buffer.len() < queue::CAPACITY {
+            task::current().notify();
+        }
 
         Ok(Async::NotReady)
     }
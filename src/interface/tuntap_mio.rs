@@ -12,7 +12,6 @@ use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
 use std::{io, mem};
 
 use super::Type;
-use super::TAP_PATH;
 use super::TUN_PATH;
 
 #[link(name = "libtuntap", kind = "static")]
@@ -22,6 +21,15 @@ extern "C" {
     /// return 0 if success.
     /// return other if failure, the value definition see man errno.
     fn setup_tun_device(fd: i32, ifname: *const c_char) -> i32;
+
+    /// set up a tap device, same as `setup_tun_device`
+    fn setup_tap_device(fd: i32, ifname: *const c_char) -> i32;
+
+    /// open one more queue of a multi-queue tun device, same as `setup_tun_device`
+    fn setup_tun_mq_device(fd: i32, ifname: *const c_char) -> i32;
+
+    /// open one more queue of a multi-queue tap device, same as `setup_tun_device`
+    fn setup_tap_mq_device(fd: i32, ifname: *const c_char) -> i32;
 }
 
 #[derive(Debug)]
@@ -31,20 +39,40 @@ pub struct TunTap {
 
 impl TunTap {
     pub fn new(device_name: &str, t: Type) -> Self {
-        info!("crate new device: {:?}, type: {:?}", device_name, t);
+        Self::open(device_name, t, false)
+    }
+
+    /// open `n` queues of the device, the kernel spread the package by flow
+    /// to them, a single queue device is opened if `n` is 1
+    pub fn queues(device_name: &str, t: Type, n: usize) -> Vec<Self> {
+        if n <= 1 {
+            return vec![Self::new(device_name, t)];
+        }
+        (0..n).map(|_| Self::open(device_name, t, true)).collect()
+    }
+
+    fn open(device_name: &str, t: Type, multi_queue: bool) -> Self {
+        info!(
+            "crate new device: {:?}, type: {:?}, multi queue: {}",
+            device_name, t, multi_queue
+        );
         let c_device_name = CString::new(device_name).unwrap();
 
         let fd = unsafe {
-            let fd = match t {
-                Type::Tun => libc::open(TUN_PATH.as_ptr(), libc::O_RDWR | libc::O_NONBLOCK),
-                Type::Tap => libc::open(TAP_PATH.as_ptr(), libc::O_RDWR | libc::O_NONBLOCK),
-            };
+            // both tun and tap are created from the clone device
+            let fd = libc::open(TUN_PATH.as_ptr(), libc::O_RDWR | libc::O_NONBLOCK);
 
             if fd < 0 {
                 panic!("open file error: {}", IoError::last_os_error());
             }
 
-            if setup_tun_device(fd, c_device_name.as_ptr()) < 0 {
+            let setup = match (t, multi_queue) {
+                (Type::Tun, false) => setup_tun_device(fd, c_device_name.as_ptr()),
+                (Type::Tap, false) => setup_tap_device(fd, c_device_name.as_ptr()),
+                (Type::Tun, true) => setup_tun_mq_device(fd, c_device_name.as_ptr()),
+                (Type::Tap, true) => setup_tap_mq_device(fd, c_device_name.as_ptr()),
+            };
+            if setup < 0 {
                 panic!(
                     "use ioctl to set tun name IO error: {}",
                     IoError::last_os_error()
@@ -17,6 +17,14 @@ impl TunTap {
         let io = PollEvented::new(TunTapMio::new(device_name, t));
         TunTap { io }
     }
+
+    /// wrap a queue opened by `TunTapMio::queues`, it is registered to the
+    /// reactor of the thread polling it
+    pub fn from_queue(queue: TunTapMio) -> Self {
+        TunTap {
+            io: PollEvented::new(queue),
+        }
+    }
 }
 
 impl Read for TunTap {
@@ -0,0 +1,255 @@
+use log::*;
+use std::cell::RefCell;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// room before the package for the headers of the envelope
+pub const HEADROOM: usize = 64;
+/// the largest package read from the device
+pub const MTU: usize = 1500;
+/// room after the package for the tag of the envelope
+pub const TAILROOM: usize = 64;
+const SIZE: usize = HEADROOM + MTU + TAILROOM;
+
+/// buffers preallocated for every thread, a buffer put back to a full pool
+/// is freed
+const CAPACITY: usize = 1024;
+
+thread_local! {
+    static POOL: RefCell<Vec<Vec<u8>>> =
+        RefCell::new((0..CAPACITY).map(|_| vec![0; SIZE]).collect());
+}
+
+/// a buffer is allocated because the pool is empty
+static EXHAUSTED: AtomicU64 = AtomicU64::new(0);
+/// a buffer is freed because the pool is full
+static OVERFLOW: AtomicU64 = AtomicU64::new(0);
+
+/// a package buffer from the pool of the thread, put back when dropped
+///
+/// the package is `data[start..end]`, the bytes before and after it can be
+/// taken by `push_front` and `push_back` to wrap it without copy
+pub struct Buffer {
+    data: Vec<u8>,
+    start: usize,
+    end: usize,
+}
+
+/// counters of all pools
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Stats {
+    pub exhausted: u64,
+    pub overflow: u64,
+}
+
+impl Buffer {
+    /// a buffer of `MTU` bytes with the headroom and tailroom
+    pub fn get() -> Self {
+        let data = POOL
+            .try_with(|p| p.borrow_mut().pop())
+            .ok()
+            .and_then(|b| b)
+            .unwrap_or_else(|| {
+                EXHAUSTED.fetch_add(1, Ordering::Relaxed);
+                trace!("buffer pool empty, allocate new buffer");
+                vec![0; SIZE]
+            });
+        Buffer {
+            data,
+            start: HEADROOM,
+            end: HEADROOM + MTU,
+        }
+    }
+
+    /// a buffer of `len` bytes, from the pool if it fit, the headroom and
+    /// tailroom is the same
+    pub fn with_len(len: usize) -> Self {
+        if len <= MTU + TAILROOM {
+            let mut buffer = Self::get();
+            buffer.set_len(len);
+            return buffer;
+        }
+        trace!("{} bytes is larger than the pool buffer, allocate it", len);
+        Buffer {
+            data: vec![0; HEADROOM + len + TAILROOM],
+            start: HEADROOM,
+            end: HEADROOM + len,
+        }
+    }
+
+    /// set the length of the package, it can grow into the tailroom
+    pub fn set_len(&mut self, len: usize) {
+        assert!(self.start + len <= self.data.len());
+        self.end = self.start + len;
+    }
+
+    pub fn headroom(&self) -> usize {
+        self.start
+    }
+
+    pub fn tailroom(&self) -> usize {
+        self.data.len() - self.end
+    }
+
+    /// grow the package `n` bytes at the front, return the new bytes
+    pub fn push_front(&mut self, n: usize) -> &mut [u8] {
+        assert!(n <= self.start);
+        self.start -= n;
+        &mut self.data[self.start..self.start + n]
+    }
+
+    /// remove `n` bytes at the front of the package
+    pub fn pull_front(&mut self, n: usize) {
+        assert!(self.start + n <= self.end);
+        self.start += n;
+    }
+
+    /// grow the package `n` bytes at the back, return the new bytes
+    pub fn push_back(&mut self, n: usize) -> &mut [u8] {
+        assert!(n <= self.tailroom());
+        self.end += n;
+        &mut self.data[self.end - n..self.end]
+    }
+
+    /// take all the headroom and tailroom, like for reading a envelope
+    pub fn expand(&mut self) {
+        self.start = 0;
+        self.end = self.data.len();
+    }
+
+    /// buffers left in the pool of this thread
+    pub fn available() -> usize {
+        POOL.try_with(|p| p.borrow().len()).unwrap_or(0)
+    }
+}
+
+/// the counters since start up
+pub fn stats() -> Stats {
+    Stats {
+        exhausted: EXHAUSTED.load(Ordering::Relaxed),
+        overflow: OVERFLOW.load(Ordering::Relaxed),
+    }
+}
+
+/// a copy of the package in a buffer from the pool
+impl Clone for Buffer {
+    fn clone(&self) -> Self {
+        let mut buffer = Self::with_len(self.len());
+        buffer.copy_from_slice(self);
+        buffer
+    }
+}
+
+/// take a buffer not from the pool, it is put back if it has the same size
+impl From<Vec<u8>> for Buffer {
+    fn from(data: Vec<u8>) -> Self {
+        let end = data.len();
+        Buffer {
+            data,
+            start: 0,
+            end,
+        }
+    }
+}
+
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        if self.data.len() != SIZE {
+            return;
+        }
+        let data = std::mem::take(&mut self.data);
+        let full = POOL
+            .try_with(|p| {
+                let mut p = p.borrow_mut();
+                if p.len() >= CAPACITY {
+                    return true;
+                }
+                p.push(data);
+                false
+            })
+            .unwrap_or(true);
+        if full {
+            OVERFLOW.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Deref for Buffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.data[self.start..self.end]
+    }
+}
+
+impl DerefMut for Buffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.data[self.start..self.end]
+    }
+}
+
+impl AsRef<[u8]> for Buffer {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl AsMut<[u8]> for Buffer {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "buffer pool exhausted {} times, overflow {} times",
+            self.exhausted, self.overflow
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn pool_and_room() {
+        let available = Buffer::available();
+        let mut b = Buffer::get();
+        assert_eq!(Buffer::available(), available - 1);
+        assert_eq!(b.len(), MTU);
+
+        b.set_len(3);
+        b.copy_from_slice(&[1, 2, 3]);
+        b.push_front(2).copy_from_slice(&[8, 9]);
+        b.push_back(1)[0] = 4;
+        assert_eq!(&b[..], &[8, 9, 1, 2, 3, 4]);
+        assert_eq!(b.headroom(), HEADROOM - 2);
+        b.pull_front(2);
+        assert_eq!(&b[..], &[1, 2, 3, 4]);
+
+        drop(b);
+        assert_eq!(Buffer::available(), available);
+
+        // a full ethernet frame still fit a pool buffer
+        let mut frame = Buffer::with_len(MTU + 14);
+        frame[MTU + 13] = 7;
+        assert_eq!(&frame.clone()[..], &frame[..]);
+        drop(frame);
+        assert_eq!(Buffer::available(), available);
+
+        // a buffer not from the pool is not put back
+        drop(Buffer::from(vec![1, 2, 3]));
+        assert_eq!(Buffer::available(), available);
+
+        let all: Vec<Buffer> = (0..=available).map(|_| Buffer::get()).collect();
+        assert_eq!(Buffer::available(), 0);
+        assert!(stats().exhausted >= 1);
+        drop(all);
+        assert_eq!(Buffer::available(), CAPACITY);
+        assert!(stats().overflow >= 1);
+    }
+}
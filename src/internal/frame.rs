@@ -0,0 +1,89 @@
+use super::buffer::Buffer;
+use crate::utils;
+use std::fmt;
+
+/// length of the ethernet header, destination, source and ether type
+pub const HEADER_LEN: usize = 14;
+
+pub type Mac = [u8; 6];
+
+/// a ethernet frame read from or write to a tap device,
+/// the counterpart of `Package` of a tun device
+#[derive(Clone)]
+pub struct Frame {
+    pub raw_frame: Buffer,
+}
+
+impl Frame {
+    #[inline]
+    pub fn from_buffer<B: Into<Buffer>>(buffer: B) -> Self {
+        Frame {
+            raw_frame: buffer.into(),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.raw_frame.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.raw_frame.is_empty()
+    }
+
+    #[inline]
+    pub fn destination(&self) -> Mac {
+        let r = &self.raw_frame;
+        assert!(r.len() >= HEADER_LEN);
+        [r[0], r[1], r[2], r[3], r[4], r[5]]
+    }
+
+    #[inline]
+    pub fn source(&self) -> Mac {
+        let r = &self.raw_frame;
+        assert!(r.len() >= HEADER_LEN);
+        [r[6], r[7], r[8], r[9], r[10], r[11]]
+    }
+
+    #[inline]
+    pub fn ether_type(&self) -> u16 {
+        assert!(self.raw_frame.len() >= HEADER_LEN);
+        utils::Reader::read_u16(&self.raw_frame[12..])
+    }
+
+    /// broadcast or multicast, like ARP request and ND solicitation
+    #[inline]
+    pub fn is_group(&self) -> bool {
+        self.destination()[0] & 1 == 1
+    }
+}
+
+pub fn mac_to_string(mac: &Mac) -> String {
+    mac.iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+impl fmt::Debug for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Frame {{ {} }}", self)
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.len() < HEADER_LEN {
+            return write!(f, "bad frame of {} bytes", self.len());
+        }
+        write!(
+            f,
+            "{} -> {} type={:04x} data={{ ...{} bytes data... }}",
+            mac_to_string(&self.source()),
+            mac_to_string(&self.destination()),
+            self.ether_type(),
+            self.len(),
+        )
+    }
+}
@@ -0,0 +1,276 @@
+use super::package::{Package, Version};
+use crate::utils::Reader;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::time::Instant;
+
+/// TTL or hop limit of the error message
+const TTL: u8 = 64;
+/// RFC 1812 4.3.2.3, a ICMP error should not be longer than 576 bytes
+const V4_MAX: usize = 576;
+/// RFC 4443 2.4 (c), a ICMPv6 error must not exceed the minimum IPv6 MTU
+const V6_MAX: usize = 1280;
+
+/// error per second to one source, and the burst
+const RATE: f64 = 10.0;
+const BURST: f64 = 10.0;
+/// clean up the idle source when track more than this
+const MAX_SOURCES: usize = 1024;
+
+const PROTOCOL_ICMP: u8 = 1;
+const NEXT_HEADER_ICMPV6: u8 = 58;
+
+/// a Time Exceeded from `source` back to the sender of `package`,
+/// `None` if the package must not be answered
+pub fn time_exceeded(package: &Package, source: IpAddr) -> Option<Package> {
+    error(package, source, (11, 0), (3, 0))
+}
+
+/// a Destination Unreachable, net unreachable of v4 or no route of v6
+pub fn net_unreachable(package: &Package, source: IpAddr) -> Option<Package> {
+    error(package, source, (3, 0), (1, 0))
+}
+
+/// token bucket of every source, RFC 1812 4.3.2.8 and RFC 4443 2.4 (f)
+#[derive(Debug, Default)]
+pub struct RateLimit {
+    buckets: HashMap<IpAddr, (f64, Instant)>,
+}
+
+impl RateLimit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// take a token of `source`, false if it run out
+    pub fn allow(&mut self, source: IpAddr) -> bool {
+        let now = Instant::now();
+        if self.buckets.len() > MAX_SOURCES {
+            self.buckets
+                .retain(|_, (tokens, last)| *tokens + refill(*last, now) < BURST);
+        }
+
+        let (tokens, last) = self.buckets.entry(source).or_insert((BURST, now));
+        *tokens = (*tokens + refill(*last, now)).min(BURST);
+        *last = now;
+        if *tokens < 1.0 {
+            return false;
+        }
+        *tokens -= 1.0;
+        true
+    }
+}
+
+fn refill(last: Instant, now: Instant) -> f64 {
+    let d = now.duration_since(last);
+    (d.as_secs() as f64 + f64::from(d.subsec_micros()) / 1_000_000.0) * RATE
+}
+
+/// build a ICMP error with `(type, code)` of v4 or v6, quote as many bytes
+/// of the original package as the limit allowed
+fn error(package: &Package, source: IpAddr, v4: (u8, u8), v6: (u8, u8)) -> Option<Package> {
+    if !should_answer(package) {
+        return None;
+    }
+    match (package.version(), source, package.source_address()) {
+        (Version::V4, IpAddr::V4(s), IpAddr::V4(d)) => {
+            Some(v4_error(&package.raw_package, s, d, v4))
+        }
+        (Version::V6, IpAddr::V6(s), IpAddr::V6(d)) => {
+            Some(v6_error(&package.raw_package, s, d, v6))
+        }
+        _ => None,
+    }
+}
+
+/// RFC 1812 4.3.2.7 and RFC 4443 2.4 (e), never answer a error, a fragment
+/// other than the first, or a package not to or from a single host
+fn should_answer(package: &Package) -> bool {
+    let r = &package.raw_package;
+    match package.version() {
+        Version::V4 => {
+            if r.len() < 20 {
+                return false;
+            }
+            let (src, dst) = match (package.source_address(), package.destination_address()) {
+                (IpAddr::V4(s), IpAddr::V4(d)) => (s, d),
+                _ => return false,
+            };
+            if src.is_unspecified() || src.is_multicast() || src.is_broadcast() {
+                return false;
+            }
+            if dst.is_multicast() || dst.is_broadcast() {
+                return false;
+            }
+            if Reader::read_u16(&r[6..]) & 0x1fff != 0 {
+                return false;
+            }
+            let header = usize::from(r[0] & 0x0f) * 4;
+            if r[9] == PROTOCOL_ICMP && r.len() > header {
+                // echo, timestamp and information are not error
+                return [0, 8, 13, 14, 15, 16].contains(&r[header]);
+            }
+            true
+        }
+        Version::V6 => {
+            if r.len() < 40 {
+                return false;
+            }
+            let (src, dst) = match (package.source_address(), package.destination_address()) {
+                (IpAddr::V6(s), IpAddr::V6(d)) => (s, d),
+                _ => return false,
+            };
+            if src.is_unspecified() || src.is_multicast() || dst.is_multicast() {
+                return false;
+            }
+            if r[6] == NEXT_HEADER_ICMPV6 && r.len() > 40 {
+                // type below 128 is error
+                return r[40] >= 128;
+            }
+            true
+        }
+    }
+}
+
+fn v4_error(original: &[u8], source: Ipv4Addr, dest: Ipv4Addr, (t, c): (u8, u8)) -> Package {
+    let quote = original.len().min(V4_MAX - 28);
+    let total = 28 + quote;
+    let mut v = vec![0u8; total];
+
+    v[0] = 0x45;
+    v[2..4].copy_from_slice(&(total as u16).to_be_bytes());
+    v[8] = TTL;
+    v[9] = PROTOCOL_ICMP;
+    v[12..16].copy_from_slice(&source.octets());
+    v[16..20].copy_from_slice(&dest.octets());
+    let check = checksum(&v[..20], 0);
+    v[10..12].copy_from_slice(&check.to_be_bytes());
+
+    v[20] = t;
+    v[21] = c;
+    v[28..].copy_from_slice(&original[..quote]);
+    let check = checksum(&v[20..], 0);
+    v[22..24].copy_from_slice(&check.to_be_bytes());
+
+    Package::from_buffer(v)
+}
+
+fn v6_error(original: &[u8], source: Ipv6Addr, dest: Ipv6Addr, (t, c): (u8, u8)) -> Package {
+    let quote = original.len().min(V6_MAX - 48);
+    let length = 8 + quote;
+    let mut v = vec![0u8; 40 + length];
+
+    v[0] = 0x60;
+    v[4..6].copy_from_slice(&(length as u16).to_be_bytes());
+    v[6] = NEXT_HEADER_ICMPV6;
+    v[7] = TTL;
+    v[8..24].copy_from_slice(&source.octets());
+    v[24..40].copy_from_slice(&dest.octets());
+
+    v[40] = t;
+    v[41] = c;
+    v[48..].copy_from_slice(&original[..quote]);
+
+    // pseudo header, RFC 8200 8.1
+    let mut pseudo = sum(&v[8..40]);
+    pseudo += length as u32;
+    pseudo += u32::from(NEXT_HEADER_ICMPV6);
+    let check = checksum(&v[40..], pseudo);
+    v[42..44].copy_from_slice(&check.to_be_bytes());
+
+    Package::from_buffer(v)
+}
+
+fn sum(data: &[u8]) -> u32 {
+    let mut s = 0u32;
+    for c in data.chunks(2) {
+        let w = if c.len() == 2 {
+            Reader::read_u16(c)
+        } else {
+            u16::from(c[0]) << 8
+        };
+        s += u32::from(w);
+    }
+    s
+}
+
+/// internet checksum of RFC 1071
+fn checksum(data: &[u8], initial: u32) -> u16 {
+    let mut s = initial + sum(data);
+    while s >> 16 != 0 {
+        s = (s & 0xffff) + (s >> 16);
+    }
+    !(s as u16)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn udp_v4(ttl: u8) -> Package {
+        let mut v = vec![0u8; 28];
+        v[0] = 0x45;
+        v[2..4].copy_from_slice(&28u16.to_be_bytes());
+        v[8] = ttl;
+        v[9] = 17;
+        v[12..16].copy_from_slice(&[10, 1, 0, 2]);
+        v[16..20].copy_from_slice(&[10, 1, 1, 2]);
+        let check = checksum(&v[..20], 0);
+        v[10..12].copy_from_slice(&check.to_be_bytes());
+        Package::from_buffer(v)
+    }
+
+    #[test]
+    pub fn time_exceeded_v4() {
+        let mut p = udp_v4(2);
+        p.set_ttl(1);
+        assert_eq!(p.ttl(), 1);
+        assert_eq!(checksum(&p.raw_package[..20], 0), 0);
+
+        let e = time_exceeded(&p, "10.1.1.1".parse().unwrap()).unwrap();
+        let r = &e.raw_package;
+        assert_eq!(r.len(), 28 + 28);
+        assert_eq!(checksum(&r[..20], 0), 0);
+        assert_eq!(checksum(&r[20..], 0), 0);
+        assert_eq!((r[20], r[21]), (11, 0));
+        assert_eq!(e.destination_address(), p.source_address());
+
+        // never answer a error
+        assert!(time_exceeded(&e, "10.1.2.1".parse().unwrap()).is_none());
+    }
+
+    #[test]
+    pub fn time_exceeded_v6() {
+        let mut v = vec![0u8; 48];
+        v[0] = 0x60;
+        v[4..6].copy_from_slice(&8u16.to_be_bytes());
+        v[6] = 17;
+        v[7] = 1;
+        v[8..24].copy_from_slice(&"fd00::2".parse::<Ipv6Addr>().unwrap().octets());
+        v[24..40].copy_from_slice(&"fd00:1::2".parse::<Ipv6Addr>().unwrap().octets());
+        let p = Package::from_buffer(v);
+
+        let source: Ipv6Addr = "fd00:1::1".parse().unwrap();
+        let e = time_exceeded(&p, source.into()).unwrap();
+        let r = &e.raw_package;
+        assert_eq!((r[40], r[41]), (3, 0));
+        assert_eq!(e.destination_address(), p.source_address());
+        let pseudo = sum(&r[8..40]) + (r.len() as u32 - 40) + 58;
+        assert_eq!(checksum(&r[40..], pseudo), 0);
+    }
+
+    #[test]
+    pub fn unreachable_rate_limit() {
+        let p = udp_v4(64);
+        let e = net_unreachable(&p, "10.1.0.1".parse().unwrap()).unwrap();
+        assert_eq!((e.raw_package[20], e.raw_package[21]), (3, 0));
+
+        let mut limit = RateLimit::new();
+        let a = p.source_address();
+        for _ in 0..BURST as usize {
+            assert!(limit.allow(a));
+        }
+        assert!(!limit.allow(a));
+        assert!(limit.allow("10.1.0.3".parse().unwrap()));
+    }
+}
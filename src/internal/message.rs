@@ -1,5 +1,8 @@
+use super::frame::Frame;
 use super::package::Package;
-use crate::generated::transport::{Node, PackageShard, Payload, PingPong};
+use crate::generated::transport::{
+    Endpoint, FrameShard, LinkState, Node, PackageShard, Payload, PingPong,
+};
 use log::*;
 use std::net::SocketAddr;
 
@@ -12,14 +15,42 @@ pub enum Message {
     PackageShareRead(Package, u32),
     PackageShareWrite(SocketAddr, Package, u32),
 
+    /// frame from and to the tap device
+    FrameRead(Frame),
+    FrameWrite(Frame),
+
+    /// address of the neighbor, origin, frame and TTL
+    FrameShareRead(SocketAddr, String, Frame, u32),
+    FrameShareWrite(SocketAddr, String, Frame, u32),
+
     AddNodeRead(SocketAddr, Node),
     AddNodeWrite(SocketAddr, Node),
 
     DelNodeRead(SocketAddr, Node),
     DelNodeWrite(SocketAddr, Node),
 
-    PingPongRead(SocketAddr, String),
-    PingPongWrite(SocketAddr, String),
+    PingPongRead(SocketAddr, PingPong),
+    PingPongWrite(SocketAddr, PingPong),
+
+    LinkStateRead(SocketAddr, LinkState),
+    LinkStateWrite(SocketAddr, LinkState),
+
+    EndpointRead(SocketAddr, Endpoint),
+    EndpointWrite(SocketAddr, Endpoint),
+
+    /// the remote finish the handshake, with its name in the allow-list
+    HandshakeRead(SocketAddr, String),
+
+    /// a authenticated package come from a new address of the remote,
+    /// the old address and the new one
+    Roamed(SocketAddr, SocketAddr),
+
+    /// withdraw myself from the network and exit,
+    /// pass from signal to router to socket
+    Shutdown,
+
+    /// read `advertise` of the config file again, pass from signal to router
+    Reload,
 
     DoNoting,
 }
@@ -34,11 +65,19 @@ impl Message {
         match self {
             Message::PackageShareWrite(a, package, ttl) => {
                 let mut package_shard = PackageShard::new();
-                package_shard.set_package(package.raw_package);
+                package_shard.set_package(package.into());
                 package_shard.set_ttl(ttl);
                 payload.set_package(package_shard);
                 addr = a;
             }
+            Message::FrameShareWrite(a, origin, frame, ttl) => {
+                let mut frame_shard = FrameShard::new();
+                frame_shard.set_frame(frame.raw_frame.to_vec());
+                frame_shard.set_ttl(ttl);
+                frame_shard.set_origin(origin);
+                payload.set_frame(frame_shard);
+                addr = a;
+            }
             Message::AddNodeWrite(a, node) => {
                 payload.set_add_node(node);
                 addr = a;
@@ -47,19 +86,34 @@ impl Message {
                 payload.set_del_node(node);
                 addr = a;
             }
-            Message::PingPongWrite(a, name) => {
-                let mut wrapper = PingPong::new();
-                wrapper.set_name(name);
-                payload.set_ping(wrapper);
+            Message::PingPongWrite(a, ping) => {
+                payload.set_ping(ping);
+                addr = a;
+            }
+            Message::LinkStateWrite(a, lsa) => {
+                payload.set_link_state(lsa);
+                addr = a;
+            }
+            Message::EndpointWrite(a, endpoint) => {
+                payload.set_endpoint(endpoint);
                 addr = a;
             }
             Message::InterfaceRead(_) => panic!("can not write InterfaceRead to bytes"),
             Message::InterfaceWrite(_) => panic!("can not write InterfaceWrite to bytes"),
+            Message::FrameRead(_) => panic!("can not write FrameRead to bytes"),
+            Message::FrameWrite(_) => panic!("can not write FrameWrite to bytes"),
+            Message::FrameShareRead(_, _, _, _) => panic!("can not write FrameShareRead to bytes"),
             Message::DoNoting => panic!("can not write DoNoting to bytes"),
             Message::PingPongRead(_, _) => panic!("can not write PingPingRead to bytes"),
             Message::AddNodeRead(_, _) => panic!("can not write AddNodeRead to bytes"),
             Message::PackageShareRead(_, _) => panic!("can not write PackageShareRead to bytes"),
             Message::DelNodeRead(_, _) => panic!("can not write DelNodeRead to bytes"),
+            Message::LinkStateRead(_, _) => panic!("can not write LinkStateRead to bytes"),
+            Message::EndpointRead(_, _) => panic!("can not write EndpointRead to bytes"),
+            Message::HandshakeRead(_, _) => panic!("can not write HandshakeRead to bytes"),
+            Message::Roamed(_, _) => panic!("can not write Roamed to bytes"),
+            Message::Shutdown => panic!("can not write Shutdown to bytes"),
+            Message::Reload => panic!("can not write Reload to bytes"),
         };
         let bytes = (Box::new(payload) as Box<protobuf::Message>)
             .write_to_bytes()
@@ -82,13 +136,27 @@ impl Message {
                 warn!("no payload, drop package");
                 (Message::DoNoting)
             }
-            Some(PayloadOneof::ping(value)) => Message::PingPongRead(addr, value.name),
+            Some(PayloadOneof::ping(value)) => Message::PingPongRead(addr, value),
             Some(PayloadOneof::package(package)) => {
                 let p = Package::from_buffer(package.package);
+                if !p.is_valid() {
+                    warn!("not a IP package from {}, drop package", addr);
+                    return Message::DoNoting;
+                }
                 Message::PackageShareRead(p, package.ttl)
             }
+            Some(PayloadOneof::frame(shard)) => {
+                let f = Frame::from_buffer(shard.frame);
+                Message::FrameShareRead(addr, shard.origin, f, shard.ttl)
+            }
             Some(PayloadOneof::add_node(node)) => Message::AddNodeRead(addr, node),
             Some(PayloadOneof::del_node(node)) => Message::DelNodeRead(addr, node),
+            Some(PayloadOneof::link_state(lsa)) => Message::LinkStateRead(addr, lsa),
+            Some(PayloadOneof::endpoint(e)) => Message::EndpointRead(addr, e),
+            Some(PayloadOneof::handshake(_)) => {
+                warn!("handshake inside a session from {}, drop package", addr);
+                Message::DoNoting
+            }
         }
     }
 }
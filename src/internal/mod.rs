@@ -1,3 +1,7 @@
+pub mod buffer;
 pub mod error;
+pub mod frame;
+pub mod icmp;
 pub mod message;
 pub mod package;
+pub mod queue;
@@ -1,13 +1,8 @@
+use super::buffer::{self, Buffer};
 use crate::utils;
-use log::*;
-use rand::AsByteSliceMut;
-use std::collections::linked_list::LinkedList;
 use std::convert::Into;
 use std::fmt;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
-use std::sync::RwLock;
-
-pub struct Buffer;
 
 #[derive(Debug)]
 pub enum Version {
@@ -21,17 +16,14 @@ pub struct Package {
     // DestinationAddress,
     // ...
     // RawData,
-    pub raw_package: Vec<u8>,
+    pub raw_package: Buffer,
 }
 
 impl Package {
     #[inline]
     pub fn as_slice(&mut self) -> &mut [u8] {
-        assert_eq!(self.raw_package.capacity() >= 1500, true);
-        unsafe {
-            self.raw_package.set_len(1500);
-        }
-        self.raw_package.as_byte_slice_mut()
+        self.raw_package.set_len(buffer::MTU);
+        &mut self.raw_package
     }
 
     #[inline]
@@ -44,12 +36,22 @@ impl Package {
         self.raw_package.is_empty()
     }
 
+    /// a IPv4 or IPv6 package at least as long as its fixed header, a package
+    /// from the network must be checked before routing
+    pub fn is_valid(&self) -> bool {
+        match self.raw_package.first().map(|b| b >> 4) {
+            Some(4) => self.len() >= 20,
+            Some(6) => self.len() >= 40,
+            _ => false,
+        }
+    }
+
     #[inline]
     pub fn version(&self) -> Version {
         match utils::Reader::read_u4_high(self.raw_package.as_ref()) {
             4 => Version::V4,
             6 => Version::V6,
-            _ => unreachable!("bad ip package: {:?}", self.raw_package),
+            _ => unreachable!("bad ip package: {:?}", &self.raw_package[..]),
         }
     }
 
@@ -88,39 +90,45 @@ impl Package {
             }
         }
     }
+
+    /// TTL of v4 or hop limit of v6
     #[inline]
-    pub fn from_buffer(buffer: Vec<u8>) -> Self {
-        Package {
-            raw_package: buffer,
+    pub fn ttl(&self) -> u8 {
+        let r = &self.raw_package;
+        match self.version() {
+            Version::V4 => r[8],
+            Version::V6 => r[7],
         }
     }
-}
 
-impl Buffer {
-    pub fn get() -> Vec<u8> {
-        let mut b = BUFFER.write().unwrap();
-        if b.is_empty() {
-            trace!("buffer empty, push new buffer");
-            b.push_back(vec![0; 1500]);
-            b.push_back(vec![0; 1500]);
-            b.push_back(vec![0; 1500]);
-            b.push_back(vec![0; 1500]);
-            b.push_back(vec![0; 1500]);
-            return vec![0; 1500];
+    /// set TTL of v4 or hop limit of v6,
+    /// the v4 header checksum is updated like RFC 1624
+    pub fn set_ttl(&mut self, ttl: u8) {
+        match self.version() {
+            Version::V4 => {
+                let r = &mut self.raw_package;
+                assert!(r.len() >= 20);
+                let old = utils::Reader::read_u16(&r[8..]);
+                r[8] = ttl;
+                let new = utils::Reader::read_u16(&r[8..]);
+                let check = utils::Reader::read_u16(&r[10..]);
+                let mut sum = u32::from(!check) + u32::from(!old) + u32::from(new);
+                while sum >> 16 != 0 {
+                    sum = (sum & 0xffff) + (sum >> 16);
+                }
+                let check = !(sum as u16);
+                r[10] = (check >> 8) as u8;
+                r[11] = check as u8;
+            }
+            Version::V6 => self.raw_package[7] = ttl,
         }
-
-        b.pop_front().unwrap()
     }
 
     #[inline]
-    pub fn set_len(buffer: &mut Vec<u8>, len: usize) {
-        unsafe { buffer.set_len(len) }
-    }
-
-    #[inline]
-    pub fn put_back(buffer: Vec<u8>) {
-        let mut b = BUFFER.write().unwrap();
-        b.push_back(buffer)
+    pub fn from_buffer<B: Into<Buffer>>(buffer: B) -> Self {
+        Package {
+            raw_package: buffer.into(),
+        }
     }
 }
 
@@ -152,7 +160,7 @@ impl fmt::Display for Package {
 
 impl Into<Vec<u8>> for Package {
     fn into(self) -> Vec<u8> {
-        self.raw_package
+        self.raw_package.to_vec()
     }
 }
 
@@ -164,7 +172,3 @@ impl fmt::Display for Version {
         }
     }
 }
-
-lazy_static! {
-    static ref BUFFER: RwLock<LinkedList<Vec<u8>>> = LinkedList::new().into();
-}
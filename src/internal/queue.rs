@@ -0,0 +1,218 @@
+use super::message::Message;
+use super::package::Package;
+use log::*;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::prelude::{Async, Stream};
+use tokio::sync::mpsc;
+
+/// data message a queue can hold, a little more than a buffer pool
+pub const CAPACITY: usize = 1024;
+
+lazy_static! {
+    static ref GAUGES: Mutex<Vec<Arc<Gauge>>> = Mutex::new(vec![]);
+}
+
+/// what a queue do when it is full
+pub trait Policy {
+    /// a data message is dropped at the tail of a full queue, the others
+    /// is control message and never dropped
+    fn is_data(&self) -> bool;
+}
+
+/// depth and drop of a queue
+#[derive(Debug)]
+pub struct Gauge {
+    pub name: &'static str,
+    depth: AtomicUsize,
+    dropped: AtomicU64,
+}
+
+/// the queue between two stages
+///
+/// data message go through a bounded channel and is dropped if it is full,
+/// so a slow peer or a stalled device can not take all the memory, control
+/// message go through a unbounded one beside it and is received first
+pub fn channel<T: Policy>(name: &'static str, capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let (data_tx, data_rx) = mpsc::channel(capacity);
+    let (control_tx, control_rx) = mpsc::unbounded_channel();
+    let gauge = gauge(name);
+    (
+        Sender {
+            data: data_tx,
+            control: control_tx,
+            gauge: gauge.clone(),
+        },
+        Receiver {
+            data: Some(data_rx),
+            control: Some(control_rx),
+            gauge,
+        },
+    )
+}
+
+/// a gauge shown with the queues, for a buffer of the stage itself
+pub fn gauge(name: &'static str) -> Arc<Gauge> {
+    let gauge = Arc::new(Gauge {
+        name,
+        depth: AtomicUsize::new(0),
+        dropped: AtomicU64::new(0),
+    });
+    GAUGES.lock().unwrap().push(gauge.clone());
+    gauge
+}
+
+/// gauges of every queue
+pub fn gauges() -> Vec<Arc<Gauge>> {
+    GAUGES.lock().unwrap().clone()
+}
+
+#[derive(Debug)]
+pub struct Sender<T> {
+    data: mpsc::Sender<T>,
+    control: mpsc::UnboundedSender<T>,
+    gauge: Arc<Gauge>,
+}
+
+#[derive(Debug)]
+pub struct Receiver<T> {
+    /// `None` after it is closed
+    data: Option<mpsc::Receiver<T>>,
+    control: Option<mpsc::UnboundedReceiver<T>>,
+    gauge: Arc<Gauge>,
+}
+
+impl<T: Policy> Sender<T> {
+    /// queue `t`, return false if it is dropped, or the receiver is gone
+    pub fn send(&mut self, t: T) -> bool {
+        // count it before the receiver can see it
+        self.gauge.depth.fetch_add(1, Ordering::Relaxed);
+        // the error is true if the queue is full
+        let result = if t.is_data() {
+            self.data.try_send(t).map_err(|e| e.is_full())
+        } else {
+            self.control.try_send(t).map_err(|_| false)
+        };
+        if result.is_err() {
+            self.gauge.depth.fetch_sub(1, Ordering::Relaxed);
+        }
+        match result {
+            Ok(()) => true,
+            Err(true) => {
+                trace!("{} queue is full, drop data", self.gauge.name);
+                self.gauge.dropped.fetch_add(1, Ordering::Relaxed);
+                false
+            }
+            Err(false) => {
+                debug!("{} queue is closed", self.gauge.name);
+                false
+            }
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Sender {
+            data: self.data.clone(),
+            control: self.control.clone(),
+            gauge: self.gauge.clone(),
+        }
+    }
+}
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+    type Error = ();
+
+    /// control message first, end when all sender is gone
+    fn poll(&mut self) -> Result<Async<Option<T>>, ()> {
+        if let Some(control) = &mut self.control {
+            match control.poll() {
+                Ok(Async::Ready(Some(t))) => {
+                    self.gauge.depth.fetch_sub(1, Ordering::Relaxed);
+                    return Ok(Async::Ready(Some(t)));
+                }
+                Ok(Async::NotReady) => {}
+                Ok(Async::Ready(None)) | Err(_) => self.control = None,
+            }
+        }
+        if let Some(data) = &mut self.data {
+            match data.poll() {
+                Ok(Async::Ready(Some(t))) => {
+                    self.gauge.depth.fetch_sub(1, Ordering::Relaxed);
+                    return Ok(Async::Ready(Some(t)));
+                }
+                Ok(Async::NotReady) => {}
+                Ok(Async::Ready(None)) | Err(_) => self.data = None,
+            }
+        }
+        if self.control.is_none() && self.data.is_none() {
+            debug!("{} queue is closed", self.gauge.name);
+            return Ok(Async::Ready(None));
+        }
+        Ok(Async::NotReady)
+    }
+}
+
+impl Gauge {
+    /// message in the queue
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    /// data message dropped since start up
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// a full buffer drop a data message
+    pub fn drop_data(&self) {
+        trace!("{} is full, drop data", self.name);
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Policy for Package {
+    fn is_data(&self) -> bool {
+        true
+    }
+}
+
+impl Policy for Message {
+    fn is_data(&self) -> bool {
+        matches!(
+            self,
+            Message::InterfaceRead(_)
+                | Message::InterfaceWrite(_)
+                | Message::PackageShareRead(_, _)
+                | Message::PackageShareWrite(_, _, _)
+                | Message::FrameRead(_)
+                | Message::FrameWrite(_)
+                | Message::FrameShareRead(_, _, _, _)
+                | Message::FrameShareWrite(_, _, _, _)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn drop_data_keep_control() {
+        let (mut tx, rx) = channel::<Message>("test", 2);
+        let data = || Message::InterfaceRead(Package::from_buffer(vec![0x45; 20]));
+        assert!(tx.send(data()));
+        assert!(tx.send(data()));
+        assert!(!tx.send(data()));
+        assert!(tx.send(Message::Reload));
+        assert_eq!(tx.gauge.depth(), 3);
+        assert_eq!(tx.gauge.dropped(), 1);
+
+        // control message first, and the end instead of a error
+        drop(tx);
+        let received: Vec<bool> = rx.wait().map(|m| m.unwrap().is_data()).collect();
+        assert_eq!(received, vec![false, true, true]);
+    }
+}
@@ -1,6 +1,7 @@
 #![feature(box_syntax)]
 
 pub mod config;
+pub mod crypto;
 pub mod generated;
 pub mod interface;
 pub mod internal;
@@ -11,14 +12,21 @@ pub mod utils;
 #[macro_use]
 extern crate lazy_static;
 
-use crate::config::ARG;
+use crate::config::{Config, ARG};
+use crate::crypto::handshake::Identity;
+use crate::crypto::{Crypto, Shared};
 use crate::interface::Device;
-use crate::network::Network;
+use crate::internal::queue;
+use crate::network::{Network, Worker};
+use crate::router::Tables;
 use env_logger::Builder;
 use env_logger::Target;
 use log::LevelFilter;
+use std::io::{self, Read};
+use std::process;
+use std::thread;
 use tokio::prelude::future::{lazy, Future};
-use tokio::sync::mpsc;
+use tokio::runtime::current_thread;
 
 fn main() {
     Builder::from_default_env()
@@ -42,12 +50,68 @@ fn main() {
         .target(Target::Stderr)
         .init();
 
-    let server = lazy(|| {
-        let (interface_io_tx, interface_io_rx) = mpsc::unbounded_channel();
-        let (message_bus_tx, message_bus_rx) = mpsc::unbounded_channel();
+    match ARG.subcommand() {
+        ("genkey", _) => {
+            println!("{}", Identity::generate().private_base64());
+            return;
+        }
+        ("pubkey", _) => {
+            let mut key = String::new();
+            io::stdin()
+                .read_to_string(&mut key)
+                .expect("read private key from stdin");
+            match Identity::from_base64(&key) {
+                Ok(identity) => println!("{}", identity.public_base64()),
+                Err(e) => {
+                    eprintln!("bad private key, {}", e);
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let (control, control_rx) = queue::channel("router", queue::CAPACITY);
+    let worker = Worker {
+        crypto: Shared::new(Crypto::from_config(Config::get())),
+        tables: Tables::default(),
+        control,
+    };
+    let mut queues = Device::open().into_iter();
+    let first = queues.next().unwrap();
+
+    // other queues are polled by a worker with a reactor of its own
+    for (i, queue) in queues.enumerate() {
+        let worker = worker.clone();
+        thread::Builder::new()
+            .name(format!("worker-{}", i + 1))
+            .spawn(move || {
+                current_thread::run(lazy(move || {
+                    let (interface_io_tx, interface_io_rx) =
+                        queue::channel("interface read", queue::CAPACITY);
+                    let (message_bus_tx, message_bus_rx) =
+                        queue::channel("interface write", queue::CAPACITY);
+
+                    let device =
+                        Device::new(message_bus_rx, interface_io_tx, queue).map_err(|_| ());
+                    let net =
+                        Network::shard(interface_io_rx, message_bus_tx, worker).map_err(|_| ());
+
+                    tokio::spawn(device);
+                    tokio::spawn(net);
+                    Ok(())
+                }))
+            })
+            .unwrap();
+    }
+
+    let server = lazy(move || {
+        let (interface_io_tx, interface_io_rx) = queue::channel("interface read", queue::CAPACITY);
+        let (message_bus_tx, message_bus_rx) = queue::channel("interface write", queue::CAPACITY);
 
-        let device = Device::new(message_bus_rx, interface_io_tx).map_err(|_| ());
-        let net = Network::new(interface_io_rx, message_bus_tx).map_err(|_| ());
+        let device = Device::new(message_bus_rx, interface_io_tx, first).map_err(|_| ());
+        let net = Network::new(interface_io_rx, message_bus_tx, worker, control_rx).map_err(|_| ());
 
         tokio::spawn(device);
         tokio::spawn(net);
@@ -0,0 +1,413 @@
+use std::io;
+use std::mem;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::ops::{Deref, DerefMut};
+use std::os::unix::io::RawFd;
+use std::ptr;
+
+/// datagrams in one `recvmmsg` or `sendmmsg`
+pub const BATCH: usize = 32;
+/// a GRO read is at most a full UDP datagram
+pub const GRO_BUFFER: usize = 65535;
+
+/// socket options of linux/udp.h
+const UDP_SEGMENT: libc::c_int = 103;
+const UDP_GRO: libc::c_int = 104;
+/// the kernel limit of segments in one GSO write
+const MAX_SEGMENTS: usize = 64;
+/// payload limit of one GSO write, a UDP datagram over IPv6
+const MAX_GSO_BYTES: usize = 65527 - 40;
+
+/// room for one control message of a int, same as `CMSG_SPACE(sizeof(int))`
+type Control = [u64; 4];
+
+/// turn on `UDP_GRO`, a read may return a train of datagrams from one peer
+pub fn enable_gro(fd: RawFd) -> io::Result<()> {
+    set_udp_option(fd, UDP_GRO, 1)
+}
+
+/// true if the kernel support `UDP_SEGMENT`
+pub fn probe_gso(fd: RawFd) -> bool {
+    set_udp_option(fd, UDP_SEGMENT, 0).is_ok()
+}
+
+fn set_udp_option(fd: RawFd, name: libc::c_int, value: libc::c_int) -> io::Result<()> {
+    let ret = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_UDP,
+            name,
+            &value as *const libc::c_int as *const libc::c_void,
+            mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// the arrays of `recvmmsg` and `sendmmsg`, kept by a socket and reused by
+/// every call, so a batch allocate nothing after the first one
+///
+/// the headers point into the other arrays only during a call
+#[derive(Default)]
+pub struct Batch {
+    headers: Vec<libc::mmsghdr>,
+    iovs: Vec<libc::iovec>,
+    addrs: Vec<(libc::sockaddr_storage, libc::socklen_t)>,
+    controls: Vec<Control>,
+    runs: Vec<usize>,
+}
+
+/// the pointers in `Batch` is not used outside of a call
+unsafe impl Send for Batch {}
+
+impl Batch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// receive up to `buffers.len()` datagrams without blocking, the
+    /// length, the sender and the segment size of every one is pushed to
+    /// `received`
+    ///
+    /// the segment size is the length unless GRO coalesced the datagrams,
+    /// return `WouldBlock` if nothing to read
+    pub fn recv<B: DerefMut<Target = [u8]>>(
+        &mut self,
+        fd: RawFd,
+        buffers: &mut [B],
+        received: &mut Vec<(usize, SocketAddr, usize)>,
+    ) -> io::Result<usize> {
+        let n = buffers.len();
+        self.addrs.clear();
+        self.addrs.resize(
+            n,
+            (
+                unsafe { mem::zeroed() },
+                mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t,
+            ),
+        );
+        self.controls.clear();
+        self.controls.resize(n, [0; 4]);
+        self.iovs.clear();
+        self.iovs.extend(buffers.iter_mut().map(|b| libc::iovec {
+            iov_base: b.as_mut_ptr() as *mut libc::c_void,
+            iov_len: b.len(),
+        }));
+
+        self.headers.clear();
+        for i in 0..n {
+            let mut h: libc::mmsghdr = unsafe { mem::zeroed() };
+            h.msg_hdr.msg_name = &mut self.addrs[i].0 as *mut _ as *mut libc::c_void;
+            h.msg_hdr.msg_namelen = self.addrs[i].1;
+            h.msg_hdr.msg_iov = &mut self.iovs[i];
+            h.msg_hdr.msg_iovlen = 1;
+            h.msg_hdr.msg_control = self.controls[i].as_mut_ptr() as *mut libc::c_void;
+            h.msg_hdr.msg_controllen = mem::size_of::<Control>() as _;
+            self.headers.push(h);
+        }
+
+        let ret = unsafe {
+            libc::recvmmsg(
+                fd,
+                self.headers.as_mut_ptr(),
+                n as libc::c_uint,
+                libc::MSG_DONTWAIT,
+                ptr::null_mut(),
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let ret = ret as usize;
+        for (h, (addr, _)) in self.headers.iter().zip(self.addrs.iter()).take(ret) {
+            // a UDP socket always give a v4 or v6 address
+            let from = from_sockaddr(addr).unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], 0)));
+            let len = h.msg_len as usize;
+            let segment = gro_segment(&h.msg_hdr).unwrap_or(len);
+            received.push((len, from, segment));
+        }
+        Ok(ret)
+    }
+
+    /// send the datagrams without blocking, return how many is sent from the
+    /// beginning, the rest should be sent later
+    ///
+    /// with `gso` a run of datagrams of the same size to one address, the
+    /// last one may be shorter, is sent as one `UDP_SEGMENT` write.
+    /// return `WouldBlock` if none can be sent
+    pub fn send<B: Deref<Target = [u8]>>(
+        &mut self,
+        fd: RawFd,
+        datagrams: &[(SocketAddr, B)],
+        gso: bool,
+    ) -> io::Result<usize> {
+        self.runs.clear();
+        if gso {
+            runs(datagrams, &mut self.runs);
+        } else {
+            self.runs.resize(datagrams.len(), 1);
+        }
+
+        self.iovs.clear();
+        self.iovs.extend(datagrams.iter().map(|(_, b)| libc::iovec {
+            iov_base: b.as_ptr() as *mut libc::c_void,
+            iov_len: b.len(),
+        }));
+        self.controls.clear();
+        self.controls.resize(self.runs.len(), [0; 4]);
+        self.addrs.clear();
+        let mut start = 0;
+        for run in &self.runs {
+            self.addrs.push(to_sockaddr(&datagrams[start].0));
+            start += run;
+        }
+
+        self.headers.clear();
+        let mut start = 0;
+        for (i, run) in self.runs.iter().enumerate() {
+            let mut h: libc::mmsghdr = unsafe { mem::zeroed() };
+            h.msg_hdr.msg_name = &mut self.addrs[i].0 as *mut _ as *mut libc::c_void;
+            h.msg_hdr.msg_namelen = self.addrs[i].1;
+            h.msg_hdr.msg_iov = &mut self.iovs[start];
+            h.msg_hdr.msg_iovlen = *run as _;
+            if *run > 1 {
+                let segment = datagrams[start].1.len() as u16;
+                set_segment(&mut h.msg_hdr, &mut self.controls[i], segment);
+            }
+            self.headers.push(h);
+            start += run;
+        }
+
+        let ret = unsafe {
+            libc::sendmmsg(
+                fd,
+                self.headers.as_mut_ptr(),
+                self.headers.len() as libc::c_uint,
+                libc::MSG_DONTWAIT,
+            )
+        };
+        if ret < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(self.runs.iter().take(ret as usize).sum())
+    }
+}
+
+/// split the datagrams into runs can be sent by one GSO write, push the
+/// length of every run to `v`
+fn runs<B: Deref<Target = [u8]>>(datagrams: &[(SocketAddr, B)], v: &mut Vec<usize>) {
+    let mut i = 0;
+    while i < datagrams.len() {
+        let (addr, first) = &datagrams[i];
+        let segment = first.len();
+        let mut total = segment;
+        let mut j = i + 1;
+        while j < datagrams.len() && j - i < MAX_SEGMENTS {
+            let (a, d) = &datagrams[j];
+            // only the last one can be shorter
+            if a != addr || d.len() > segment || datagrams[j - 1].1.len() != segment {
+                break;
+            }
+            if total + d.len() > MAX_GSO_BYTES {
+                break;
+            }
+            total += d.len();
+            j += 1;
+        }
+        v.push(j - i);
+        i = j;
+    }
+}
+
+fn cmsg_align(len: usize) -> usize {
+    let a = mem::size_of::<usize>();
+    (len + a - 1) & !(a - 1)
+}
+
+/// add a `UDP_SEGMENT` control message of `segment` to `msg`
+fn set_segment(msg: &mut libc::msghdr, control: &mut Control, segment: u16) {
+    let header = cmsg_align(mem::size_of::<libc::cmsghdr>());
+    let base = control.as_mut_ptr() as *mut u8;
+    unsafe {
+        let cmsg = base as *mut libc::cmsghdr;
+        (*cmsg).cmsg_len = (header + mem::size_of::<u16>()) as _;
+        (*cmsg).cmsg_level = libc::SOL_UDP;
+        (*cmsg).cmsg_type = UDP_SEGMENT;
+        ptr::write_unaligned(base.add(header) as *mut u16, segment);
+    }
+    msg.msg_control = base as *mut libc::c_void;
+    msg.msg_controllen = (header + cmsg_align(mem::size_of::<u16>())) as _;
+}
+
+/// the segment size of a read coalesced by GRO
+fn gro_segment(msg: &libc::msghdr) -> Option<usize> {
+    let header = cmsg_align(mem::size_of::<libc::cmsghdr>());
+    let base = msg.msg_control as *const u8;
+    let end = msg.msg_controllen;
+    let mut offset = 0;
+    while offset + header <= end {
+        let cmsg = unsafe { &*(base.add(offset) as *const libc::cmsghdr) };
+        let len = cmsg.cmsg_len;
+        if len < header {
+            break;
+        }
+        if cmsg.cmsg_level == libc::SOL_UDP && cmsg.cmsg_type == UDP_GRO {
+            let segment = unsafe { ptr::read_unaligned(base.add(offset + header) as *const i32) };
+            return if segment > 0 {
+                Some(segment as usize)
+            } else {
+                None
+            };
+        }
+        offset += cmsg_align(len);
+    }
+    None
+}
+
+fn to_sockaddr(addr: &SocketAddr) -> (libc::sockaddr_storage, libc::socklen_t) {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let len = match addr {
+        SocketAddr::V4(a) => {
+            let sin = libc::sockaddr_in {
+                sin_family: libc::AF_INET as libc::sa_family_t,
+                sin_port: a.port().to_be(),
+                sin_addr: libc::in_addr {
+                    s_addr: u32::from_ne_bytes(a.ip().octets()),
+                },
+                sin_zero: [0; 8],
+            };
+            unsafe { ptr::write(&mut storage as *mut _ as *mut libc::sockaddr_in, sin) };
+            mem::size_of::<libc::sockaddr_in>()
+        }
+        SocketAddr::V6(a) => {
+            let sin6 = libc::sockaddr_in6 {
+                sin6_family: libc::AF_INET6 as libc::sa_family_t,
+                sin6_port: a.port().to_be(),
+                sin6_flowinfo: a.flowinfo(),
+                sin6_addr: libc::in6_addr {
+                    s6_addr: a.ip().octets(),
+                },
+                sin6_scope_id: a.scope_id(),
+            };
+            unsafe { ptr::write(&mut storage as *mut _ as *mut libc::sockaddr_in6, sin6) };
+            mem::size_of::<libc::sockaddr_in6>()
+        }
+    };
+    (storage, len as libc::socklen_t)
+}
+
+fn from_sockaddr(storage: &libc::sockaddr_storage) -> Option<SocketAddr> {
+    match i32::from(storage.ss_family) {
+        libc::AF_INET => {
+            let sin = unsafe { &*(storage as *const _ as *const libc::sockaddr_in) };
+            let ip = Ipv4Addr::from(sin.sin_addr.s_addr.to_ne_bytes());
+            Some(SocketAddrV4::new(ip, u16::from_be(sin.sin_port)).into())
+        }
+        libc::AF_INET6 => {
+            let sin6 = unsafe { &*(storage as *const _ as *const libc::sockaddr_in6) };
+            let ip = Ipv6Addr::from(sin6.sin6_addr.s6_addr);
+            Some(
+                SocketAddrV6::new(
+                    ip,
+                    u16::from_be(sin6.sin6_port),
+                    sin6.sin6_flowinfo,
+                    sin6.sin6_scope_id,
+                )
+                .into(),
+            )
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::UdpSocket;
+    use std::os::unix::io::AsRawFd;
+
+    #[test]
+    pub fn batch_loopback() {
+        let a = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let b = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let to = b.local_addr().unwrap();
+
+        let datagrams: Vec<(SocketAddr, Vec<u8>)> =
+            (1..=3u8).map(|i| (to, vec![i; usize::from(i)])).collect();
+        let mut batch = Batch::new();
+        assert_eq!(batch.send(a.as_raw_fd(), &datagrams, false).unwrap(), 3);
+
+        let mut buffers = vec![vec![0u8; 1500]; BATCH];
+        let mut received = vec![];
+        let n = batch
+            .recv(b.as_raw_fd(), &mut buffers, &mut received)
+            .unwrap();
+        assert_eq!(n, 3);
+        for (i, (len, from, segment)) in received.iter().enumerate() {
+            assert_eq!(*len, i + 1);
+            assert_eq!(*segment, *len);
+            assert_eq!(*from, a.local_addr().unwrap());
+            assert_eq!(buffers[i][0], i as u8 + 1);
+        }
+
+        let e = batch
+            .recv(b.as_raw_fd(), &mut buffers, &mut received)
+            .unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::WouldBlock);
+    }
+
+    #[test]
+    pub fn gso_runs() {
+        let a: SocketAddr = "192.0.2.1:1".parse().unwrap();
+        let b: SocketAddr = "192.0.2.2:1".parse().unwrap();
+        let d = |addr, len| (addr, vec![0u8; len]);
+        let datagrams = vec![
+            d(a, 100),
+            d(a, 100),
+            d(a, 60),
+            d(a, 100),
+            d(b, 100),
+            d(b, 200),
+        ];
+        let mut v = vec![];
+        runs(&datagrams, &mut v);
+        assert_eq!(v, vec![3, 1, 1, 1]);
+
+        let many: Vec<_> = (0..100).map(|_| d(a, 1200)).collect();
+        v.clear();
+        runs(&many, &mut v);
+        assert_eq!(v, vec![54, 46]);
+    }
+
+    #[test]
+    pub fn gso_loopback() {
+        let a = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let b = UdpSocket::bind("127.0.0.1:0").unwrap();
+        if !probe_gso(a.as_raw_fd()) {
+            return;
+        }
+        let to = b.local_addr().unwrap();
+        let datagrams: Vec<_> = [100, 100, 100, 40]
+            .iter()
+            .map(|len| (to, vec![7u8; *len]))
+            .collect();
+        let mut batch = Batch::new();
+        assert_eq!(batch.send(a.as_raw_fd(), &datagrams, true).unwrap(), 4);
+
+        // without GRO the kernel split it back
+        let mut buffers = vec![vec![0u8; 1500]; BATCH];
+        let mut received = vec![];
+        assert_eq!(
+            batch
+                .recv(b.as_raw_fd(), &mut buffers, &mut received)
+                .unwrap(),
+            4
+        );
+        let lens: Vec<usize> = received.iter().map(|r| r.0).collect();
+        assert_eq!(lens, vec![100, 100, 100, 40]);
+    }
+}
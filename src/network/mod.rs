@@ -1,30 +1,22 @@
+pub mod mmsg;
 pub mod socket;
 
 use crate::config::Config;
+use crate::crypto;
 use crate::generated::transport as proto;
 use crate::internal::message::Message;
-use crate::internal::package::Package;
+use crate::internal::queue;
 use crate::network::socket::Socket;
-use crate::router::{Host, Router};
+use crate::router::{Host, Metric, Router, Tables};
 use log::*;
 use std::convert::From;
 use std::io;
 use std::net::SocketAddr;
-use tokio::prelude::future::lazy;
 use tokio::prelude::Stream;
 use tokio::prelude::{Async, Future};
-use tokio::sync::mpsc;
+use tokio_signal::unix::Signal;
 
 lazy_static! {
-    pub static ref SELF_SHARE: proto::Node = {
-        let c = Config::get();
-        let mut myself = proto::Node::new();
-        myself.set_sub_net(c.get_v4().octets().to_vec());
-        myself.set_net_mask(c.get_v4_mask());
-        myself.set_name(c.name.clone());
-        myself.set_jump(0);
-        myself
-    };
     pub static ref SELF_INIT: proto::Node = {
         let c = Config::get();
         let mut myself = proto::Node::new();
@@ -36,56 +28,127 @@ lazy_static! {
     };
 }
 pub struct Network {
-    interface_receiver: mpsc::UnboundedReceiver<Package>,
-    interface_send: mpsc::UnboundedSender<Package>,
+    interface_receiver: queue::Receiver<Message>,
+    interface_send: queue::Sender<Message>,
 
-    router_send: mpsc::UnboundedSender<Message>,
-    router_receiver: mpsc::UnboundedReceiver<Message>,
+    router_send: queue::Sender<Message>,
+    router_receiver: queue::Receiver<Message>,
+
+    socket_send: queue::Sender<Message>,
+    socket_receiver: queue::Receiver<Message>,
+}
 
-    socket_send: mpsc::UnboundedSender<Message>,
-    socket_receiver: mpsc::UnboundedReceiver<Message>,
+/// what a worker thread share with the others
+///
+/// every worker poll a queue of the device and its own socket on the same
+/// port, and route the package with a router on the shared tables. the first
+/// worker also run the control plane, the router of other worker pass
+/// everything else than a package to it
+#[derive(Clone)]
+pub struct Worker {
+    pub crypto: crypto::Shared,
+    pub tables: Tables,
+    /// channel to the router of the first worker
+    pub control: queue::Sender<Message>,
 }
 
 impl Network {
-    pub fn new(rx: mpsc::UnboundedReceiver<Package>, tx: mpsc::UnboundedSender<Package>) -> Self {
+    /// the network of the first worker, `control` is the receiver of
+    /// `worker.control`
+    pub fn new(
+        rx: queue::Receiver<Message>,
+        tx: queue::Sender<Message>,
+        worker: Worker,
+        control: queue::Receiver<Message>,
+    ) -> Self {
         let c = Config::get();
 
-        let (sender_to_router, _r) = mpsc::unbounded_channel();
-        let (_s, receiver_from_router) = mpsc::unbounded_channel();
-        let router = Router::new(_s, _r);
+        let sender_to_router = worker.control.clone();
+        let (_s, receiver_from_router) = queue::channel("router write", queue::CAPACITY);
+        let router = Router::with_tables(_s, control, worker.tables, None);
 
-        let (mut sender_to_socket, _r) = mpsc::unbounded_channel();
-        let (_s, receiver_from_socket) = mpsc::unbounded_channel();
-        let socket = Socket::new(_s, _r);
+        let (mut sender_to_socket, _r) = queue::channel("socket write", queue::CAPACITY);
+        let (_s, receiver_from_socket) = queue::channel("socket read", queue::CAPACITY);
+        let socket = Socket::new(_s, _r, worker.crypto);
 
         // add my self to router table
-        router
-            .insert_to_table(
-                c.get_v4().into(),
-                c.get_v4_mask() as u16,
+        for (addr, mask) in c.get_announce() {
+            let result = router.insert_to_table(
+                addr,
+                mask,
                 c.name.clone(),
                 Host::Localhost,
-            )
-            .unwrap();
+                Metric::new(0, 0),
+            );
+            if result.is_err() {
+                warn!("{}/{} is already in the table", addr, mask);
+            }
+        }
 
         // prepare hello message to other node
         // clone for all servers
         for host in &c.servers {
             if host.name != c.name {
                 let addr = SocketAddr::new(host.address.parse().unwrap(), host.port);
-                sender_to_socket
-                    .try_send(Message::AddNodeWrite(addr, SELF_INIT.clone()))
-                    .unwrap();
+                sender_to_socket.send(Message::AddNodeWrite(addr, SELF_INIT.clone()));
             }
         }
 
-        tokio::spawn(lazy(|| {
-            router.map_err(|e| {
-                error!("{:?}", e);
-                panic!(e);
-            })
-        }));
+        tokio::spawn(router);
+
+        tokio::spawn(socket);
+
+        // announce and withdraw the `advertise` again
+        let mut reload = sender_to_router.clone();
+        tokio::spawn(
+            Signal::new(libc::SIGHUP)
+                .flatten_stream()
+                .map_err(|e| error!("signal error {}", e))
+                .for_each(move |_| {
+                    info!("get SIGHUP, reload config");
+                    reload.send(Message::Reload);
+                    Ok(())
+                }),
+        );
+
+        // withdraw myself before exit
+        let mut shutdown = sender_to_router.clone();
+        let ctrl_c = tokio_signal::ctrl_c().flatten_stream();
+        let term = Signal::new(libc::SIGTERM).flatten_stream().map(|_| ());
+        tokio::spawn(
+            ctrl_c
+                .select(term)
+                .into_future()
+                .map_err(|(e, _)| error!("signal error {}", e))
+                .map(move |_| {
+                    info!("get signal, shutting down");
+                    shutdown.send(Message::Shutdown);
+                }),
+        );
+
+        Network {
+            interface_receiver: rx,
+            interface_send: tx,
+            socket_send: sender_to_socket,
+            socket_receiver: receiver_from_socket,
+            router_send: sender_to_router,
+            router_receiver: receiver_from_router,
+        }
+    }
+}
+
+impl Network {
+    /// the network of other worker, only forward package
+    pub fn shard(rx: queue::Receiver<Message>, tx: queue::Sender<Message>, worker: Worker) -> Self {
+        let (sender_to_router, _r) = queue::channel("router", queue::CAPACITY);
+        let (_s, receiver_from_router) = queue::channel("router write", queue::CAPACITY);
+        let router = Router::with_tables(_s, _r, worker.tables, Some(worker.control));
+
+        let (sender_to_socket, _r) = queue::channel("socket write", queue::CAPACITY);
+        let (_s, receiver_from_socket) = queue::channel("socket read", queue::CAPACITY);
+        let socket = Socket::new(_s, _r, worker.crypto);
 
+        tokio::spawn(router);
         tokio::spawn(socket);
 
         Network {
@@ -105,49 +168,50 @@ impl Future for Network {
 
     fn poll(&mut self) -> Result<Async<Self::Item>, Self::Error> {
         loop {
-            match self.router_receiver.poll()? {
-                Async::Ready(Some(message)) => match message {
+            match self.router_receiver.poll() {
+                Ok(Async::Ready(Some(message))) => match message {
                     Message::DoNoting => {}
-                    Message::InterfaceWrite(package) => {
-                        self.interface_send.try_send(package).unwrap();
+                    m @ Message::InterfaceWrite(_) | m @ Message::FrameWrite(_) => {
+                        self.interface_send.send(m);
+                    }
+                    m @ Message::PackageShareWrite(_, _, _) => {
+                        self.socket_send.send(m);
                     }
-                    Message::PackageShareWrite(addr, package, ttl) => self
-                        .socket_send
-                        .try_send(Message::PackageShareWrite(addr, package, ttl))
-                        .unwrap(),
-                    Message::AddNodeWrite(addr, node) => {
-                        self.socket_send
-                            .try_send(Message::AddNodeWrite(addr, node))
-                            .unwrap();
+                    m @ Message::AddNodeWrite(_, _)
+                    | m @ Message::DelNodeWrite(_, _)
+                    | m @ Message::PingPongWrite(_, _)
+                    | m @ Message::LinkStateWrite(_, _)
+                    | m @ Message::EndpointWrite(_, _)
+                    | m @ Message::FrameShareWrite(_, _, _, _)
+                    | m @ Message::Shutdown => {
+                        self.socket_send.send(m);
                     }
                     other => {
-                        self.router_send.try_send(other).unwrap();
+                        self.router_send.send(other);
                     }
                 },
-                Async::Ready(None) => {
-                    panic!();
-                }
-                Async::NotReady => break,
+                Ok(Async::NotReady) => break,
+                Ok(Async::Ready(None)) | Err(()) => return closed("router"),
             }
         }
 
         loop {
-            match self.socket_receiver.poll()? {
-                Async::Ready(Some(message)) => self.router_send.try_send(message).unwrap(),
-                Async::Ready(None) => panic!(),
-                Async::NotReady => break,
+            match self.socket_receiver.poll() {
+                Ok(Async::Ready(Some(message))) => {
+                    self.router_send.send(message);
+                }
+                Ok(Async::NotReady) => break,
+                Ok(Async::Ready(None)) | Err(()) => return closed("socket"),
             }
         }
 
         loop {
-            match self.interface_receiver.poll()? {
-                Async::Ready(Some(package)) => {
-                    self.router_send
-                        .try_send(Message::InterfaceRead(package))
-                        .unwrap();
+            match self.interface_receiver.poll() {
+                Ok(Async::Ready(Some(m))) => {
+                    self.router_send.send(m);
                 }
-                Async::Ready(None) => panic!(),
-                Async::NotReady => break,
+                Ok(Async::NotReady) => break,
+                Ok(Async::Ready(None)) | Err(()) => return closed("device"),
             };
         }
 
@@ -155,15 +219,14 @@ impl Future for Network {
     }
 }
 
-pub enum Error {
-    IoError(io::Error),
-    RecvError(mpsc::error::UnboundedRecvError),
+/// a stage is gone, finish the network instead of panic
+fn closed(stage: &str) -> Result<Async<()>, Error> {
+    warn!("{} is gone, stop the network", stage);
+    Ok(Async::Ready(()))
 }
 
-impl From<mpsc::error::UnboundedRecvError> for Error {
-    fn from(e: mpsc::error::UnboundedRecvError) -> Self {
-        Error::RecvError(e)
-    }
+pub enum Error {
+    IoError(io::Error),
 }
 
 impl From<io::Error> for Error {
@@ -1,50 +1,149 @@
+use super::mmsg::{self, BATCH};
 use crate::config::Config;
+use crate::crypto::{Shared as Crypto, TYPE_DATA};
+use crate::interface::Device;
+use crate::internal::buffer::Buffer;
 use crate::internal::message::Message;
-use crate::internal::package::Buffer;
+use crate::internal::package::Package;
+use crate::internal::queue;
 use log::*;
+use net2::unix::UnixUdpBuilderExt;
 use net2::UdpBuilder;
-use std::collections::linked_list::LinkedList;
-use std::net::{IpAddr, SocketAddr};
-use tokio::net::UdpSocket;
+use std::collections::VecDeque;
+use std::io;
+use std::net::SocketAddr;
+use std::os::unix::io::AsRawFd;
+use std::process;
+use std::time::{Duration, Instant};
+use tokio::prelude::task;
 use tokio::prelude::Stream;
 use tokio::prelude::{Async, Future};
-use tokio::reactor::Handle;
-use tokio::sync::mpsc;
+use tokio::reactor::{Handle, PollEvented2};
+use tokio::timer::Delay;
+
+/// datagrams in one read with GRO, every one can hold 64KB
+const GRO_BATCH: usize = 8;
+/// wait for the handshakes of the datagrams queued in crypto before exit
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// a non-blocking UDP socket with the offloads the kernel support
+struct Udp {
+    io: PollEvented2<mio::net::UdpSocket>,
+    /// send a run of datagrams to one address by a `UDP_SEGMENT` write
+    gso: bool,
+    /// the read buffers, kept for every poll, they hold coalesced reads if
+    /// `UDP_GRO` is supported
+    buffers: Vec<Buffer>,
+    /// the length, sender and segment size of a read into `buffers`
+    received: Vec<(usize, SocketAddr, usize)>,
+    batch: mmsg::Batch,
+}
+
+impl Udp {
+    fn new(socket: std::net::UdpSocket) -> Self {
+        let fd = socket.as_raw_fd();
+        let gso = mmsg::probe_gso(fd);
+        let gro = match mmsg::enable_gro(fd) {
+            Ok(()) => true,
+            Err(e) => {
+                info!("UDP GRO is not supported, {}", e);
+                false
+            }
+        };
+        info!("UDP GSO {}, GRO {} on {:?}", gso, gro, socket.local_addr());
+        let buffers: Vec<Buffer> = if gro {
+            (0..GRO_BATCH)
+                .map(|_| Buffer::from(vec![0; mmsg::GRO_BUFFER]))
+                .collect()
+        } else {
+            // a envelope is longer than the package inside
+            (0..BATCH)
+                .map(|_| {
+                    let mut buffer = Buffer::get();
+                    buffer.expand();
+                    buffer
+                })
+                .collect()
+        };
+        let received = Vec::with_capacity(buffers.len());
+        let socket = mio::net::UdpSocket::from_socket(socket).unwrap();
+        let io = PollEvented2::new_with_handle(socket, &Handle::default()).unwrap();
+        Self {
+            io,
+            gso,
+            buffers,
+            received,
+            batch: mmsg::Batch::new(),
+        }
+    }
+}
 
 pub struct Socket {
-    v6: UdpSocket,
-    v4: UdpSocket,
-    tx: mpsc::UnboundedSender<Message>,
-    rx: mpsc::UnboundedReceiver<Message>,
-    buffer: LinkedList<(SocketAddr, Vec<u8>)>,
+    v6: Udp,
+    v4: Udp,
+    tx: queue::Sender<Message>,
+    rx: queue::Receiver<Message>,
+    /// datagrams wait for the socket, at most `queue::CAPACITY`
+    buffer: VecDeque<(SocketAddr, Buffer)>,
+    crypto: Crypto,
+    /// exit after all buffered package is sent, and the ones queued in crypto
+    /// or the deadline
+    shutdown: Option<Delay>,
 }
 
 impl Socket {
-    pub fn new(tx: mpsc::UnboundedSender<Message>, rx: mpsc::UnboundedReceiver<Message>) -> Self {
+    pub fn new(tx: queue::Sender<Message>, rx: queue::Receiver<Message>, crypto: Crypto) -> Self {
         let c = Config::get();
+        // the socket of every worker bind the same port, the kernel spread
+        // the datagram by the address of the peer
+        let reuse_port = c.workers() > 1;
+
         let v6_addr = SocketAddr::new("::".parse().unwrap(), c.port);
         info!("bind to {}", v6_addr);
         let v6 = UdpBuilder::new_v6()
             .unwrap()
             .only_v6(true)
             .unwrap()
+            .reuse_port(reuse_port)
+            .unwrap()
             .bind(v6_addr)
             .unwrap();
 
         let v4_addr = SocketAddr::new("0.0.0.0".parse().unwrap(), c.port);
         info!("bind to {}", v4_addr);
-        let v4 = UdpBuilder::new_v4().unwrap().bind(v4_addr).unwrap();
+        let v4 = UdpBuilder::new_v4()
+            .unwrap()
+            .reuse_port(reuse_port)
+            .unwrap()
+            .bind(v4_addr)
+            .unwrap();
+
+        if c.fwmark != 0 {
+            info!("mark the sockets with {}", c.fwmark);
+            set_mark(&v6, c.fwmark).unwrap();
+            set_mark(&v4, c.fwmark).unwrap();
+        }
 
-        let v6 = UdpSocket::from_std(v6, &Handle::default()).unwrap();
-        let v4 = UdpSocket::from_std(v4, &Handle::default()).unwrap();
+        let v6 = Udp::new(v6);
+        let v4 = Udp::new(v4);
 
-        let buffer = LinkedList::new();
+        let buffer = VecDeque::new();
         Self {
             v6,
             v4,
             tx,
             rx,
             buffer,
+            crypto,
+            shutdown: None,
+        }
+    }
+
+    /// seal a protobuf message, it is queued in crypto if no session yet
+    fn seal(&mut self, message: Message) {
+        let (addr, plain) = message.write_bytes();
+        if let Some(package) = self.crypto.seal(addr, &plain) {
+            self.buffer.push_back((addr, Buffer::from(package)));
         }
     }
 
@@ -55,82 +154,231 @@ impl Socket {
     }
 }
 
-impl Future for Socket {
-    type Item = ();
-    type Error = ();
+/// `SO_MARK` of the socket, a policy route can match it and keep the
+/// encrypted datagrams out of the tunnel, like with a default route through it
+fn set_mark(socket: &std::net::UdpSocket, mark: u32) -> Result<(), std::io::Error> {
+    let ret = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_MARK,
+            &mark as *const u32 as *const libc::c_void,
+            std::mem::size_of::<u32>() as libc::socklen_t,
+        )
+    };
+    if ret < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
 
-    fn poll(&mut self) -> Result<Async<Self::Item>, Self::Error> {
-        loop {
-            let mut buffer = Buffer::get();
-            match self.v4.poll_recv_from(buffer.as_mut_slice()) {
-                Ok(Async::Ready((read_size, addr))) => {
-                    Buffer::set_len(buffer.as_mut(), read_size);
-                    info!("receive {} bytes from {}", read_size, addr);
-                    let message_to_router = Message::from_protobuf(addr, buffer);
-                    self.tx.try_send(message_to_router).unwrap();
-                }
-                Ok(Async::NotReady) => {
-                    Buffer::put_back(buffer);
-                    break;
+/// read every datagram of `socket` in batches by `recvmmsg`, a coalesced
+/// read of GRO is split back into the datagrams, open them and pass to the
+/// router
+fn receive(socket: &mut Udp, crypto: &Crypto, tx: &mut queue::Sender<Message>) {
+    let fd = socket.io.get_ref().as_raw_fd();
+    loop {
+        match socket.io.poll_read_ready(mio::Ready::readable()) {
+            Ok(Async::Ready(_)) => {}
+            Ok(Async::NotReady) => break,
+            Err(e) => {
+                error!("{:?}", e);
+                break;
+            }
+        }
+
+        socket.received.clear();
+        match socket
+            .batch
+            .recv(fd, &mut socket.buffers, &mut socket.received)
+        {
+            Ok(_) => {
+                for (i, (size, addr, segment)) in socket.received.iter().enumerate() {
+                    for data in socket.buffers[i][..*size].chunks(*segment) {
+                        open(crypto, tx, *addr, data);
+                    }
                 }
-                Err(e) => {
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                // register the task for the next datagram
+                if let Err(e) = socket.io.clear_read_ready(mio::Ready::readable()) {
                     error!("{:?}", e);
                 }
-            };
+                break;
+            }
+            Err(e) => {
+                error!("{:?}", e);
+                break;
+            }
         }
+    }
+}
 
-        loop {
-            let mut buffer = Buffer::get();
-            match self.v6.poll_recv_from(buffer.as_mut_slice()) {
-                Ok(Async::Ready((read_size, addr))) => {
-                    Buffer::set_len(buffer.as_mut(), read_size);
-                    info!("receive {} bytes from {}", read_size, addr);
-                    let message_to_router = Message::from_protobuf(addr, buffer);
-                    self.tx.try_send(message_to_router).unwrap();
-                }
-                Ok(Async::NotReady) => {
-                    Buffer::put_back(buffer);
-                    break;
+/// the first byte tell a data envelope from a protobuf one
+fn open(crypto: &Crypto, tx: &mut queue::Sender<Message>, addr: SocketAddr, data: &[u8]) {
+    trace!("receive {} bytes from {}", data.len(), addr);
+    if data.first() == Some(&TYPE_DATA) {
+        match crypto.open_data(addr, data) {
+            Ok((package, ttl)) => {
+                let package = Package::from_buffer(package);
+                if !package.is_valid() {
+                    warn!("drop package from {}, not a IP package", addr);
+                    return;
                 }
-                Err(e) => {
-                    error!("{:?}", e);
-                }
-            };
+                tx.send(Message::PackageShareRead(package, u32::from(ttl)));
+            }
+            Err(e) => warn!("drop package from {}, {}", addr, e),
+        }
+        return;
+    }
+    match crypto.open(addr, data) {
+        Ok(Some(plain)) => {
+            let message_to_router = Message::from_protobuf(addr, plain);
+            tx.send(message_to_router);
+        }
+        Ok(None) => {}
+        Err(e) => warn!("drop package from {}, {}", addr, e),
+    }
+}
+
+/// send `batch` by `sendmmsg`, return how many is sent or dropped from the
+/// beginning, 0 if the socket is not ready and the task is registered
+fn send(socket: &mut Udp, batch: &[(SocketAddr, Buffer)]) -> usize {
+    match socket.io.poll_write_ready() {
+        Ok(Async::Ready(_)) => {}
+        Ok(Async::NotReady) => return 0,
+        Err(e) => {
+            error!("{:?}", e);
+            return 0;
+        }
+    }
+    let fd = socket.io.get_ref().as_raw_fd();
+    let result = match socket.batch.send(fd, batch, socket.gso) {
+        // the device or the route may not support the offload, like a
+        // checksum offload is off, send them one by one from now on
+        Err(ref e) if socket.gso && e.kind() != io::ErrorKind::WouldBlock => {
+            warn!("UDP GSO is turned off, {}", e);
+            socket.gso = false;
+            socket.batch.send(fd, batch, false)
+        }
+        result => result,
+    };
+    match result {
+        Ok(n) => {
+            trace!("write {} datagrams", n);
+            n
         }
+        Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+            if let Err(e) = socket.io.clear_write_ready() {
+                error!("{:?}", e);
+            }
+            0
+        }
+        Err(e) => {
+            // drop the one can not be sent, same as `sendto`
+            error!("{:?}, drop datagram to {}", e, batch[0].0);
+            1
+        }
+    }
+}
+
+impl Future for Socket {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Result<Async<Self::Item>, Self::Error> {
+        receive(&mut self.v4, &self.crypto, &mut self.tx);
+        receive(&mut self.v6, &self.crypto, &mut self.tx);
 
-        loop {
+        // stop reading when full, so the queue before it fill and drop
+        while self.buffer.len() < queue::CAPACITY {
             match self.rx.poll() {
-                Ok(Async::Ready(None)) => panic!(),
+                Ok(Async::Ready(None)) | Err(()) => {
+                    warn!("network is gone, stop the socket");
+                    return Ok(Async::Ready(()));
+                }
+                Ok(Async::Ready(Some(Message::Shutdown))) => {
+                    info!("socket will exit after flush");
+                    self.shutdown = Some(Delay::new(Instant::now() + SHUTDOWN_TIMEOUT));
+                }
+                Ok(Async::Ready(Some(Message::PackageShareWrite(addr, package, ttl)))) => {
+                    // a package is sent with the data header, protobuf only
+                    // before the session is up
+                    let ttl_byte = ttl.min(u32::from(u8::MAX)) as u8;
+                    match self.crypto.seal_data(addr, ttl_byte, package.raw_package) {
+                        Ok(sealed) => self.buffer.push_back((addr, sealed)),
+                        Err(raw) => {
+                            let message =
+                                Message::PackageShareWrite(addr, Package::from_buffer(raw), ttl);
+                            self.seal(message);
+                        }
+                    }
+                }
                 Ok(Async::Ready(Some(message))) => {
                     if let Message::AddNodeWrite(addr, _) = message {
                         self.connect(&addr).unwrap();
                     }
-
-                    self.buffer.push_back(message.write_bytes());
+                    self.seal(message);
                 }
                 Ok(Async::NotReady) => break,
-                Err(e) => error!("{:?}", e),
             }
         }
 
-        loop {
-            match self.buffer.pop_front() {
-                None => break,
-
-                Some((addr, buffer)) => {
-                    let socket = match addr.ip() {
-                        IpAddr::V4(_) => &mut self.v4,
-                        IpAddr::V6(_) => &mut self.v6,
-                    };
-                    match socket.poll_send_to(&buffer, &addr) {
-                        Ok(Async::Ready(size)) => info!("write {} bytes to {}", size, &addr),
-                        Ok(Async::NotReady) => {
-                            self.buffer.push_back((addr, buffer));
-                            break;
-                        }
-                        Err(e) => error!("{:?}", e),
-                    };
+        {
+            let mut crypto = self.crypto.lock();
+            while let Some((addr, name)) = crypto.pop_established() {
+                self.tx.send(Message::HandshakeRead(addr, name));
+            }
+            while let Some((old, new)) = crypto.pop_roamed() {
+                self.tx.send(Message::Roamed(old, new));
+            }
+            while self.buffer.len() < queue::CAPACITY {
+                match crypto.pop_outgoing() {
+                    Some((addr, package)) => self.buffer.push_back((addr, Buffer::from(package))),
+                    None => break,
+                }
+            }
+        }
+        let full = self.buffer.len() >= queue::CAPACITY;
+
+        while !self.buffer.is_empty() {
+            // a batch of datagrams to the same address family from the front
+            let v6 = self.buffer[0].0.is_ipv6();
+            let n = self
+                .buffer
+                .iter()
+                .take(BATCH)
+                .take_while(|(a, _)| a.is_ipv6() == v6)
+                .count();
+            let batch: Vec<(SocketAddr, Buffer)> = self.buffer.drain(..n).collect();
+            let socket = if v6 { &mut self.v6 } else { &mut self.v4 };
+            let sent = send(socket, &batch);
+            for datagram in batch.into_iter().skip(sent).rev() {
+                self.buffer.push_front(datagram);
+            }
+            if sent == 0 {
+                break;
+            }
+        }
+        // the queue and crypto is not read when full, poll them again
+        if full && self.buffer.len() < queue::CAPACITY {
+            task::current().notify();
+        }
+
+        if let Some(deadline) = &mut self.shutdown {
+            let expired = match deadline.poll() {
+                Ok(Async::NotReady) => false,
+                Ok(Async::Ready(())) | Err(_) => true,
+            };
+            let queued = self.crypto.lock().queued();
+            if self.buffer.is_empty() && (queued == 0 || expired) {
+                if queued > 0 {
+                    warn!("{} package still wait for handshake, exit", queued);
+                } else {
+                    info!("all package sent, exit");
                 }
+                Device::down();
+                process::exit(0);
             }
         }
 
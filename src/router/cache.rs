@@ -0,0 +1,126 @@
+use super::peer::Host;
+use super::table::{LikeRouter, Table};
+use fnv::FnvHashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// destinations a cache remember, it is cleared when full
+const CAPACITY: usize = 4096;
+
+/// where a package to a destination go, what `forward` need from a `Peer`
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum NextHop {
+    Localhost,
+    Unreachable,
+    /// the real address, direct or the relay
+    Socket(SocketAddr),
+}
+
+/// the next hop of recent destinations, in front of the shared tables
+///
+/// every router has its own, a hit is a hash lookup without the lock of the
+/// table. it is cleared when the generation of any table changed, a entry
+/// added while the table is changing is cleared by the next lookup
+#[derive(Debug)]
+pub struct RouteCache {
+    routes: FnvHashMap<IpAddr, NextHop>,
+    generations: Vec<Arc<AtomicU64>>,
+    /// the sum of the generations when `routes` is valid, they only grow
+    seen: u64,
+}
+
+impl RouteCache {
+    pub fn new(tables: &[&Table]) -> Self {
+        Self {
+            routes: FnvHashMap::default(),
+            generations: tables.iter().map(|t| t.generation()).collect(),
+            seen: 0,
+        }
+    }
+
+    /// the cached next hop of `dest`
+    pub fn get(&mut self, dest: IpAddr) -> Option<NextHop> {
+        let generation = self
+            .generations
+            .iter()
+            .map(|g| g.load(Ordering::Acquire))
+            .sum();
+        if generation != self.seen {
+            self.routes.clear();
+            self.seen = generation;
+        }
+        self.routes.get(&dest).copied()
+    }
+
+    /// look up `dest` in `table` and cache it, after a miss of `get`
+    pub fn resolve(&mut self, dest: IpAddr, table: &Table) -> Option<NextHop> {
+        let hop = table.find(dest).map(|p| NextHop::from(&p.get_host()))?;
+        if self.routes.len() >= CAPACITY {
+            self.routes.clear();
+        }
+        self.routes.insert(dest, hop);
+        Some(hop)
+    }
+}
+
+impl From<&Host> for NextHop {
+    fn from(host: &Host) -> Self {
+        match host {
+            Host::Localhost => NextHop::Localhost,
+            Host::Unreachable => NextHop::Unreachable,
+            Host::Socket(addr) | Host::Relay(addr, _) => NextHop::Socket(*addr),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::router::peer::Metric;
+
+    #[test]
+    pub fn invalidate_by_generation() {
+        let mut table = Table::new();
+        let a = Host::Socket("192.0.2.1:1234".parse().unwrap());
+        let net = "128.66.1.0".parse().unwrap();
+        let dest = "128.66.1.1".parse().unwrap();
+        table.insert(net, 24, "a".to_string(), a.clone()).unwrap();
+
+        let mut cache = RouteCache::new(&[&table]);
+        assert_eq!(cache.get(dest), None);
+        let hop = cache.resolve(dest, &table);
+        assert_eq!(hop, Some(NextHop::from(&a)));
+        assert_eq!(cache.get(dest), hop);
+
+        table.delete(net, 24).unwrap();
+        assert_eq!(cache.get(dest), None);
+        assert_eq!(cache.resolve(dest, &table), None);
+    }
+
+    #[test]
+    pub fn follow_metric_update() {
+        let mut table = Table::new();
+        let a = Host::Socket("192.0.2.1:1234".parse().unwrap());
+        let b = Host::Socket("192.0.2.2:1234".parse().unwrap());
+        let net = "128.66.1.0".parse().unwrap();
+        let dest = "128.66.1.1".parse().unwrap();
+        table
+            .insert_with_metric(net, 24, "a".to_string(), a.clone(), Metric::new(10, 0))
+            .unwrap();
+        table
+            .insert_with_metric(net, 24, "a".to_string(), b.clone(), Metric::new(20, 0))
+            .unwrap();
+
+        let mut cache = RouteCache::new(&[&table]);
+        cache.get(dest);
+        assert_eq!(cache.resolve(dest, &table), Some(NextHop::from(&a)));
+
+        // a known host only get its metric updated, but it is not the best now
+        assert!(table
+            .insert_with_metric(net, 24, "a".to_string(), a.clone(), Metric::new(30, 0))
+            .is_err());
+        assert_eq!(cache.get(dest), None);
+        assert_eq!(cache.resolve(dest, &table), Some(NextHop::from(&b)));
+    }
+}
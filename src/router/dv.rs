@@ -0,0 +1,327 @@
+use log::*;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+/// metric of a unreachable route, a update with it is a retraction
+pub const INFINITY: u32 = u32::MAX;
+
+/// a announced subnet, address and mask
+pub type Prefix = (IpAddr, u16);
+
+/// what to do with the route table after a update from a neighbor
+#[derive(Debug, Eq, PartialEq)]
+pub enum Decision {
+    /// newer sequence number, the sender become the only next hop
+    Replace,
+    /// same sequence number and feasible, add the sender or update its metric
+    Update,
+    /// not feasible or retracted by the sender, it can not be a next hop any more
+    Drop,
+    /// retracted with a newer sequence number, remove the whole route
+    Retract,
+    /// older sequence number, or a retraction of a unknown prefix
+    Ignore,
+}
+
+/// sequence number and feasibility distance of a prefix,
+/// same as the source table of babel
+#[derive(Debug)]
+pub struct Source {
+    /// the node announce this prefix
+    pub origin: String,
+    pub seqno: u32,
+    /// smallest metric advertised with `seqno`, `None` if not advertised yet
+    pub fd: Option<u32>,
+    /// jumps from the origin of the selected route
+    pub jump: i32,
+    /// last time a feasible update is received
+    pub updated: Instant,
+    /// time of the retraction, the prefix is kept for a while to hold down
+    /// the sequence number
+    pub retracted: Option<Instant>,
+}
+
+/// the distance-vector state beside the route table
+///
+/// a update is feasible when its sequence number is newer, or the sequence
+/// number is the same and the metric is smaller than any metric this node
+/// advertised for it, so a route never go through a node depends on us
+#[derive(Debug)]
+pub struct DistanceVector {
+    name: String,
+    /// sequence number of my own prefix, bumped every full update
+    seqno: u32,
+    sources: HashMap<Prefix, Source>,
+    /// origins of the default route in order of preference
+    preferred: Vec<String>,
+}
+
+impl DistanceVector {
+    pub fn new(name: String, seqno: u32) -> Self {
+        Self {
+            name,
+            seqno,
+            sources: HashMap::new(),
+            preferred: vec![],
+        }
+    }
+
+    /// prefer the default route of the former exit node in `names`
+    pub fn prefer(&mut self, names: Vec<String>) {
+        self.preferred = names;
+    }
+
+    /// announce `prefix` as my own
+    pub fn originate(&mut self, prefix: Prefix) {
+        self.sources.insert(
+            prefix,
+            Source {
+                origin: self.name.clone(),
+                seqno: self.seqno,
+                fd: Some(0),
+                jump: 0,
+                updated: Instant::now(),
+                retracted: None,
+            },
+        );
+    }
+
+    /// stop announce my own `prefix`, call it after the retraction is sent
+    pub fn withdraw(&mut self, prefix: &Prefix) {
+        if self.is_own(prefix) {
+            self.sources.remove(prefix);
+        }
+    }
+
+    /// increase the sequence number of my own prefix, the new one
+    /// replace every route to it
+    pub fn bump(&mut self) -> u32 {
+        self.seqno = self.seqno.wrapping_add(1);
+        for s in self.sources.values_mut() {
+            if s.origin == self.name {
+                s.seqno = self.seqno;
+                s.updated = Instant::now();
+            }
+        }
+        self.seqno
+    }
+
+    pub fn is_own(&self, prefix: &Prefix) -> bool {
+        self.sources
+            .get(prefix)
+            .map_or(false, |s| s.origin == self.name)
+    }
+
+    /// decide a update of `prefix` from a neighbor, `metric` is the total
+    /// metric through that neighbor, `INFINITY` for a retraction
+    pub fn decide(
+        &mut self,
+        prefix: Prefix,
+        origin: &str,
+        seqno: u32,
+        metric: u32,
+        jump: i32,
+    ) -> Decision {
+        let now = Instant::now();
+        let s = match self.sources.get_mut(&prefix) {
+            Some(s) => s,
+            None if metric == INFINITY => return Decision::Ignore,
+            None => {
+                self.sources.insert(
+                    prefix,
+                    Source {
+                        origin: origin.to_string(),
+                        seqno,
+                        fd: None,
+                        jump,
+                        updated: now,
+                        retracted: None,
+                    },
+                );
+                return Decision::Replace;
+            }
+        };
+
+        if s.origin != origin {
+            // a default route is announced by every exit node, switch to
+            // other one when the current one is gone or a better one is up
+            let takeover = s.origin != self.name
+                && metric != INFINITY
+                && (s.retracted.is_some()
+                    || (prefix.1 == 0
+                        && rank(&self.preferred, origin) < rank(&self.preferred, &s.origin)));
+            if !takeover {
+                debug!(
+                    "{:?} is announced by {}, drop the one from {}",
+                    prefix, s.origin, origin
+                );
+                return Decision::Drop;
+            }
+            info!(
+                "{:?} is announced by {} now, not {}",
+                prefix, origin, s.origin
+            );
+            *s = Source {
+                origin: origin.to_string(),
+                seqno,
+                fd: None,
+                jump,
+                updated: now,
+                retracted: None,
+            };
+            return Decision::Replace;
+        }
+
+        if newer(seqno, s.seqno) {
+            s.seqno = seqno;
+            s.fd = None;
+            s.jump = jump;
+            s.updated = now;
+            if metric == INFINITY {
+                s.retracted = Some(now);
+                return Decision::Retract;
+            }
+            s.retracted = None;
+            return Decision::Replace;
+        }
+        if seqno != s.seqno {
+            trace!("old sequence number {} of {:?}", seqno, prefix);
+            return Decision::Ignore;
+        }
+
+        if metric != INFINITY && s.fd.map_or(true, |fd| metric < fd) {
+            s.jump = jump;
+            s.updated = now;
+            s.retracted = None;
+            Decision::Update
+        } else {
+            Decision::Drop
+        }
+    }
+
+    /// `metric` is advertised for `prefix`, lower the feasibility distance
+    pub fn advertised(&mut self, prefix: &Prefix, metric: u32) {
+        if metric == INFINITY {
+            return;
+        }
+        if let Some(s) = self.sources.get_mut(prefix) {
+            s.fd = Some(s.fd.map_or(metric, |fd| fd.min(metric)));
+        }
+    }
+
+    /// no next hop left for `prefix`
+    pub fn retract(&mut self, prefix: &Prefix) {
+        if let Some(s) = self.sources.get_mut(prefix) {
+            if s.retracted.is_none() {
+                s.retracted = Some(Instant::now());
+            }
+        }
+    }
+
+    /// return the prefix not updated for `timeout` and should be retracted,
+    /// a prefix retracted for `timeout` is forgot
+    pub fn expire(&mut self, timeout: Duration) -> Vec<Prefix> {
+        let name = &self.name;
+        self.sources.retain(|p, s| match s.retracted {
+            Some(t) if s.origin != *name && t.elapsed() > timeout => {
+                debug!("forget {:?} of {}", p, s.origin);
+                false
+            }
+            _ => true,
+        });
+
+        let mut stale = vec![];
+        for (p, s) in self.sources.iter_mut() {
+            if s.origin != self.name && s.retracted.is_none() && s.updated.elapsed() > timeout {
+                info!("{:?} of {} is not updated for {:?}", p, s.origin, timeout);
+                s.retracted = Some(Instant::now());
+                stale.push(*p);
+            }
+        }
+        stale
+    }
+
+    pub fn get(&self, prefix: &Prefix) -> Option<&Source> {
+        self.sources.get(prefix)
+    }
+
+    pub fn prefixes(&self) -> Vec<Prefix> {
+        self.sources.keys().cloned().collect()
+    }
+}
+
+/// position of `origin` in `preferred`, smaller is better
+fn rank(preferred: &[String], origin: &str) -> usize {
+    preferred
+        .iter()
+        .position(|n| n == origin)
+        .unwrap_or(preferred.len())
+}
+
+/// `a` is newer than `b`, compared in modular arithmetic
+pub fn newer(a: u32, b: u32) -> bool {
+    a != b && a.wrapping_sub(b) < 1 << 31
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn feasibility() {
+        let p: Prefix = ("128.66.2.0".parse().unwrap(), 24);
+        let mut dv = DistanceVector::new("me".to_string(), 0);
+
+        assert_eq!(dv.decide(p, "a", 10, INFINITY, 1), Decision::Ignore);
+        assert_eq!(dv.decide(p, "a", 10, 100, 1), Decision::Replace);
+        assert_eq!(dv.decide(p, "b", 11, 50, 1), Decision::Drop);
+        dv.advertised(&p, 100);
+
+        // same sequence number must be strictly better than what we advertised
+        assert_eq!(dv.decide(p, "a", 10, 80, 2), Decision::Update);
+        assert_eq!(dv.decide(p, "a", 10, 100, 2), Decision::Drop);
+        assert_eq!(dv.decide(p, "a", 10, INFINITY, 2), Decision::Drop);
+        assert_eq!(dv.decide(p, "a", 9, 10, 1), Decision::Ignore);
+
+        // a newer sequence number reset the feasibility distance
+        assert_eq!(dv.decide(p, "a", 11, 300, 3), Decision::Replace);
+        assert_eq!(dv.get(&p).unwrap().fd, None);
+        assert_eq!(dv.decide(p, "a", 12, INFINITY, 3), Decision::Retract);
+        assert!(dv.get(&p).unwrap().retracted.is_some());
+    }
+
+    #[test]
+    pub fn exit_failover() {
+        let p: Prefix = ("0.0.0.0".parse().unwrap(), 0);
+        let mut dv = DistanceVector::new("me".to_string(), 0);
+        dv.prefer(vec!["a".to_string(), "b".to_string()]);
+
+        assert_eq!(dv.decide(p, "b", 5, 100, 1), Decision::Replace);
+        assert_eq!(dv.decide(p, "c", 5, 10, 1), Decision::Drop);
+        // a preferred exit node take over
+        assert_eq!(dv.decide(p, "a", 1, 300, 1), Decision::Replace);
+        assert_eq!(dv.get(&p).unwrap().origin, "a");
+        assert_eq!(dv.decide(p, "b", 6, 100, 1), Decision::Drop);
+
+        // fail over to any one when it is gone
+        assert_eq!(dv.decide(p, "a", 2, INFINITY, 1), Decision::Retract);
+        assert_eq!(dv.decide(p, "c", 5, 10, 1), Decision::Replace);
+        assert_eq!(dv.get(&p).unwrap().origin, "c");
+    }
+
+    #[test]
+    pub fn sequence_wrap() {
+        assert!(newer(1, 0));
+        assert!(newer(0, u32::MAX));
+        assert!(!newer(0, 1));
+        assert!(!newer(5, 5));
+
+        let mut dv = DistanceVector::new("me".to_string(), u32::MAX);
+        let p: Prefix = ("128.66.1.0".parse().unwrap(), 24);
+        dv.originate(p);
+        assert!(dv.is_own(&p));
+        assert_eq!(dv.bump(), 0);
+        assert_eq!(dv.get(&p).unwrap().seqno, 0);
+    }
+}
@@ -0,0 +1,189 @@
+use log::*;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// what happened to a address after a tick or a pong
+#[derive(Debug, Eq, PartialEq)]
+pub enum Event {
+    /// miss too many pong, mark as `Host::Unreachable`
+    Down(SocketAddr),
+    /// answer again after down
+    Up(SocketAddr),
+    /// down for too long, remove it from the table
+    Dead(SocketAddr),
+}
+
+/// liveness of one real address
+#[derive(Debug)]
+pub struct Liveness {
+    pub last_seen: Option<Instant>,
+    /// smoothed round trip time
+    pub rtt: Option<Duration>,
+    /// ping sent without pong in a row
+    pub misses: u32,
+    /// smoothed loss rate, 0.0 to 1.0
+    pub loss: f64,
+    pub down: bool,
+    outstanding: Option<(u64, Instant)>,
+}
+
+/// keepalive state of every address in the router table
+///
+/// every tick a ping is sent to each address, a ping not answered before the
+/// next tick is a miss
+#[derive(Debug)]
+pub struct Keepalive {
+    hosts: HashMap<SocketAddr, Liveness>,
+    sequence: u64,
+    /// misses to mark a address down
+    down_after: u32,
+    /// misses to remove a address
+    dead_after: u32,
+}
+
+impl Keepalive {
+    pub fn new(down_after: u32, dead_after: u32) -> Self {
+        Self {
+            hosts: HashMap::new(),
+            sequence: 0,
+            down_after,
+            dead_after,
+        }
+    }
+
+    /// count the misses of last round, return the pings to send and the events
+    ///
+    /// `addrs` is all the address in the router table, state of address not
+    /// in it is dropped
+    pub fn tick(&mut self, addrs: &[SocketAddr]) -> (Vec<(SocketAddr, u64)>, Vec<Event>) {
+        let now = Instant::now();
+        self.hosts.retain(|a, _| addrs.contains(a));
+
+        let mut pings = Vec::with_capacity(addrs.len());
+        let mut events = vec![];
+        for addr in addrs {
+            let (down_after, dead_after) = (self.down_after, self.dead_after);
+            let l = self.hosts.entry(*addr).or_insert_with(Liveness::new);
+
+            if l.outstanding.take().is_some() {
+                l.misses += 1;
+                l.loss = l.loss * 7.0 / 8.0 + 1.0 / 8.0;
+                debug!("{} miss {} ping", addr, l.misses);
+            }
+            if l.misses >= dead_after {
+                warn!("{} not answer {} ping, remove it", addr, l.misses);
+                events.push(Event::Dead(*addr));
+                continue;
+            }
+            if l.misses >= down_after && !l.down {
+                warn!("{} not answer {} ping, mark it down", addr, l.misses);
+                l.down = true;
+                events.push(Event::Down(*addr));
+            }
+
+            self.sequence += 1;
+            l.outstanding = Some((self.sequence, now));
+            pings.push((*addr, self.sequence));
+        }
+
+        for e in &events {
+            if let Event::Dead(addr) = e {
+                self.hosts.remove(addr);
+            }
+        }
+        (pings, events)
+    }
+
+    /// get a pong, return `Event::Up` if the address come back
+    pub fn pong(&mut self, addr: SocketAddr, sequence: u64) -> Option<Event> {
+        let l = self.hosts.get_mut(&addr)?;
+        match l.outstanding {
+            Some((s, sent)) if s == sequence => {
+                let sample = sent.elapsed();
+                l.rtt = Some(match l.rtt {
+                    // same as TCP SRTT, alpha = 1/8
+                    Some(rtt) => rtt * 7 / 8 + sample / 8,
+                    None => sample,
+                });
+                l.loss = l.loss * 7.0 / 8.0;
+                l.outstanding = None;
+            }
+            _ => {
+                trace!("stale pong {} from {}", sequence, addr);
+                return None;
+            }
+        }
+        self.seen(addr)
+    }
+
+    /// any authenticated message from `addr` prove it is alive
+    pub fn seen(&mut self, addr: SocketAddr) -> Option<Event> {
+        let l = self.hosts.get_mut(&addr)?;
+        l.last_seen = Some(Instant::now());
+        l.misses = 0;
+        if l.down {
+            info!("{} is back", addr);
+            l.down = false;
+            return Some(Event::Up(addr));
+        }
+        None
+    }
+
+    pub fn get(&self, addr: &SocketAddr) -> Option<&Liveness> {
+        self.hosts.get(addr)
+    }
+}
+
+impl Liveness {
+    /// link cost used by `Metric::link`, RTT in milliseconds weighted by the
+    /// expected transmissions like ETX, `None` if not measured yet
+    pub fn cost(&self) -> Option<u32> {
+        let rtt = self.rtt?;
+        let ms = rtt.as_secs() as f64 * 1000.0 + f64::from(rtt.subsec_micros()) / 1000.0;
+        let etx = 1.0 / (1.0 - self.loss.min(0.9));
+        Some((ms.max(1.0) * etx).round() as u32)
+    }
+
+    fn new() -> Self {
+        Self {
+            last_seen: None,
+            rtt: None,
+            misses: 0,
+            loss: 0.0,
+            down: false,
+            outstanding: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn down_up_dead() {
+        let a: SocketAddr = "128.66.1.0:1234".parse().unwrap();
+        let mut k = Keepalive::new(2, 4);
+
+        let (pings, events) = k.tick(&[a]);
+        assert!(events.is_empty());
+        let (_, sequence) = pings[0];
+        assert_eq!(k.pong(a, sequence), None);
+        assert!(k.get(&a).unwrap().rtt.is_some());
+
+        k.tick(&[a]);
+        assert!(k.tick(&[a]).1.is_empty());
+        let (pings, events) = k.tick(&[a]);
+        assert_eq!(events, vec![Event::Down(a)]);
+        assert_eq!(k.pong(a, pings[0].1), Some(Event::Up(a)));
+
+        for _ in 0..4 {
+            k.tick(&[a]);
+        }
+        let (pings, events) = k.tick(&[a]);
+        assert!(pings.is_empty());
+        assert_eq!(events, vec![Event::Dead(a)]);
+        assert!(k.get(&a).is_none());
+    }
+}
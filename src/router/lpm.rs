@@ -0,0 +1,277 @@
+/// longest prefix match over 128 bits keys, IPv4 address is put at the high
+/// 32 bits
+///
+/// a path-compressed binary trie, a node is only created where two prefix
+/// split, so a lookup visit at most one node for every stored prefix on the
+/// path and never allocate. nodes live in one `Vec` and link by index, a
+/// deleted node is reused by the next insert. the values is kept apart from
+/// the nodes, so a lookup only touch the small nodes until it finish
+#[derive(Debug)]
+pub struct Lpm<T> {
+    nodes: Vec<Node>,
+    /// the value of `nodes[i]`, `None` for a node only split the path
+    values: Vec<Option<T>>,
+    root: Option<u32>,
+    free: Vec<u32>,
+}
+
+#[derive(Debug)]
+struct Node {
+    /// the prefix, bits after `len` is zero
+    key: u128,
+    children: [Option<u32>; 2],
+    len: u8,
+    value: bool,
+}
+
+pub const WIDTH: u8 = 128;
+
+/// the high `len` bits
+pub fn mask(len: u8) -> u128 {
+    if len == 0 {
+        0
+    } else {
+        !0u128 << (WIDTH - len)
+    }
+}
+
+/// bit `i` from the high end, `i` < 128
+fn bit(key: u128, i: u8) -> usize {
+    ((key >> (WIDTH - 1 - i)) & 1) as usize
+}
+
+impl<T> Default for Lpm<T> {
+    fn default() -> Self {
+        Self {
+            nodes: vec![],
+            values: vec![],
+            root: None,
+            free: vec![],
+        }
+    }
+}
+
+impl<T> Lpm<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the value of the longest prefix cover `key`
+    pub fn longest_match(&self, key: u128) -> Option<&T> {
+        let mut best = None;
+        let mut link = self.root;
+        while let Some(i) = link {
+            let n = &self.nodes[i as usize];
+            if key & mask(n.len) != n.key {
+                break;
+            }
+            if n.value {
+                best = Some(i);
+            }
+            if n.len == WIDTH {
+                break;
+            }
+            link = n.children[bit(key, n.len)];
+        }
+        self.values[best? as usize].as_ref()
+    }
+
+    /// the value of exactly `key/len`
+    pub fn get(&self, key: u128, len: u8) -> Option<&T> {
+        let i = self.position(key, len)?;
+        self.values[i as usize].as_ref()
+    }
+
+    pub fn get_mut(&mut self, key: u128, len: u8) -> Option<&mut T> {
+        let i = self.position(key, len)?;
+        self.values[i as usize].as_mut()
+    }
+
+    /// insert `key/len`, return the old value
+    pub fn insert(&mut self, key: u128, len: u8, value: T) -> Option<T> {
+        let key = key & mask(len);
+        let (root, old) = self.insert_at(self.root, key, len, value);
+        self.root = Some(root);
+        old
+    }
+
+    /// remove `key/len` and the node only split the path for it
+    pub fn remove(&mut self, key: u128, len: u8) -> Option<T> {
+        let key = key & mask(len);
+        let (root, old) = self.remove_at(self.root, key, len);
+        self.root = root;
+        old
+    }
+
+    /// every prefix and its value, in no order
+    pub fn iter(&self) -> impl Iterator<Item = ((u128, u8), &T)> {
+        self.nodes
+            .iter()
+            .zip(self.values.iter())
+            .filter_map(|(n, v)| v.as_ref().map(|v| ((n.key, n.len), v)))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = ((u128, u8), &mut T)> {
+        self.nodes
+            .iter()
+            .zip(self.values.iter_mut())
+            .filter_map(|(n, v)| v.as_mut().map(|v| ((n.key, n.len), v)))
+    }
+
+    fn position(&self, key: u128, len: u8) -> Option<u32> {
+        let key = key & mask(len);
+        let mut link = self.root;
+        while let Some(i) = link {
+            let n = &self.nodes[i as usize];
+            if n.len > len || key & mask(n.len) != n.key {
+                return None;
+            }
+            if n.len == len {
+                return Some(i);
+            }
+            link = n.children[bit(key, n.len)];
+        }
+        None
+    }
+
+    /// insert below `link`, return the node take its place
+    fn insert_at(&mut self, link: Option<u32>, key: u128, len: u8, value: T) -> (u32, Option<T>) {
+        let i = match link {
+            None => return (self.alloc(key, len, Some(value)), None),
+            Some(i) => i as usize,
+        };
+        let (n_key, n_len) = (self.nodes[i].key, self.nodes[i].len);
+        let common = n_len.min(len).min((n_key ^ key).leading_zeros() as u8);
+
+        if common == n_len && common == len {
+            self.nodes[i].value = true;
+            return (i as u32, self.values[i].replace(value));
+        }
+        if common == n_len {
+            // below this node
+            let b = bit(key, n_len);
+            let (child, old) = self.insert_at(self.nodes[i].children[b], key, len, value);
+            self.nodes[i].children[b] = Some(child);
+            return (i as u32, old);
+        }
+
+        // split the path at `common`
+        let j = if common == len {
+            self.alloc(key, len, Some(value))
+        } else {
+            let j = self.alloc(key & mask(common), common, None);
+            let leaf = self.alloc(key, len, Some(value));
+            self.nodes[j as usize].children[bit(key, common)] = Some(leaf);
+            j
+        };
+        self.nodes[j as usize].children[bit(n_key, common)] = Some(i as u32);
+        (j, None)
+    }
+
+    /// remove below `link`, return the node take its place
+    fn remove_at(&mut self, link: Option<u32>, key: u128, len: u8) -> (Option<u32>, Option<T>) {
+        let i = match link {
+            None => return (None, None),
+            Some(i) => i as usize,
+        };
+        let n = &self.nodes[i];
+        if n.len > len || key & mask(n.len) != n.key {
+            return (link, None);
+        }
+        let old = if n.len == len {
+            self.nodes[i].value = false;
+            self.values[i].take()
+        } else {
+            let b = bit(key, n.len);
+            let (child, old) = self.remove_at(n.children[b], key, len);
+            self.nodes[i].children[b] = child;
+            old
+        };
+        if old.is_none() {
+            return (link, None);
+        }
+        (self.compact(i), old)
+    }
+
+    /// drop node `i` if it has no value and can not split the path
+    fn compact(&mut self, i: usize) -> Option<u32> {
+        let n = &self.nodes[i];
+        if n.value {
+            return Some(i as u32);
+        }
+        let next = match n.children {
+            [Some(_), Some(_)] => return Some(i as u32),
+            [Some(c), None] | [None, Some(c)] => Some(c),
+            [None, None] => None,
+        };
+        self.nodes[i].children = [None, None];
+        self.free.push(i as u32);
+        next
+    }
+
+    fn alloc(&mut self, key: u128, len: u8, value: Option<T>) -> u32 {
+        let node = Node {
+            key,
+            children: [None, None],
+            len,
+            value: value.is_some(),
+        };
+        match self.free.pop() {
+            Some(i) => {
+                self.nodes[i as usize] = node;
+                self.values[i as usize] = value;
+                i
+            }
+            None => {
+                self.nodes.push(node);
+                self.values.push(value);
+                (self.nodes.len() - 1) as u32
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn v4(a: [u8; 4]) -> u128 {
+        u128::from(u32::from_be_bytes(a)) << 96
+    }
+
+    #[test]
+    pub fn match_and_compact() {
+        let mut t = Lpm::new();
+        t.insert(v4([10, 0, 0, 0]), 8, "a");
+        t.insert(v4([10, 1, 0, 0]), 16, "b");
+        t.insert(v4([10, 1, 2, 0]), 24, "c");
+        t.insert(v4([10, 128, 0, 0]), 16, "d");
+        t.insert(0, 0, "default");
+
+        assert_eq!(t.longest_match(v4([10, 1, 2, 3])), Some(&"c"));
+        assert_eq!(t.longest_match(v4([10, 1, 3, 3])), Some(&"b"));
+        assert_eq!(t.longest_match(v4([10, 2, 0, 1])), Some(&"a"));
+        assert_eq!(t.longest_match(v4([10, 128, 9, 1])), Some(&"d"));
+        assert_eq!(t.longest_match(v4([11, 0, 0, 1])), Some(&"default"));
+        assert_eq!(t.get(v4([10, 1, 0, 0]), 16), Some(&"b"));
+        assert_eq!(t.get(v4([10, 1, 0, 0]), 15), None);
+        assert_eq!(t.insert(v4([10, 1, 0, 0]), 16, "e"), Some("b"));
+
+        assert_eq!(t.remove(v4([10, 1, 0, 0]), 16), Some("e"));
+        assert_eq!(t.remove(v4([10, 1, 0, 0]), 16), None);
+        assert_eq!(t.longest_match(v4([10, 1, 3, 3])), Some(&"a"));
+        assert_eq!(t.longest_match(v4([10, 1, 2, 3])), Some(&"c"));
+
+        for &(key, len) in &[(0, 0), (v4([10, 0, 0, 0]), 8), (v4([10, 1, 2, 0]), 24)] {
+            t.remove(key, len).unwrap();
+        }
+        assert_eq!(t.longest_match(v4([10, 1, 2, 3])), None);
+        assert_eq!(t.iter().count(), 1);
+
+        // the split nodes is reused, not leaked
+        let nodes = t.nodes.len();
+        t.insert(v4([10, 1, 2, 0]), 24, "c");
+        t.insert(v4([10, 1, 3, 0]), 24, "f");
+        assert_eq!(t.nodes.len(), nodes);
+    }
+}
@@ -0,0 +1,323 @@
+use super::dv::{newer, Prefix};
+use super::{parse_ip, read_ip};
+use crate::generated::transport::{LinkState, LinkState_Link, LinkState_Subnet};
+use log::*;
+use protobuf::RepeatedField;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::time::Instant;
+
+/// result of a link-state advertisement from a neighbor
+#[derive(Debug, Eq, PartialEq)]
+pub enum Receive {
+    /// stored or flushed, flood it to other neighbors
+    Newer,
+    /// already have it
+    Same,
+    /// we have a newer one, send it back
+    Older,
+    /// my own from a earlier run, originate again with a newer sequence number
+    Own,
+}
+
+/// next hop and the total cost to a node
+#[derive(Debug, Eq, PartialEq)]
+pub struct Path {
+    pub next: String,
+    pub cost: u32,
+}
+
+#[derive(Debug)]
+struct Entry {
+    lsa: LinkState,
+    received: Instant,
+}
+
+/// every link-state advertisement known, include my own
+///
+/// a link is only used when both side announce it. a advertisement is not
+/// signed by its origin, any authenticated neighbor can send one for other
+/// node, see the trust model in README
+#[derive(Debug)]
+pub struct LinkStateDb {
+    name: String,
+    seqno: u32,
+    /// seconds a advertisement live without refresh
+    max_age: u32,
+    /// do not forward for other node
+    leaf: bool,
+    entries: HashMap<String, Entry>,
+}
+
+impl LinkStateDb {
+    pub fn new(name: String, seqno: u32, max_age: u32) -> Self {
+        Self {
+            name,
+            seqno,
+            max_age,
+            leaf: false,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// a leaf is never a transit node in the path of other node
+    pub fn set_leaf(&mut self, leaf: bool) {
+        self.leaf = leaf;
+    }
+
+    /// build my own advertisement with a new sequence number and store it
+    pub fn originate(&mut self, links: &[(String, u32)], subnets: &[Prefix]) -> LinkState {
+        self.seqno = self.seqno.wrapping_add(1);
+
+        let mut lsa = LinkState::new();
+        lsa.set_origin(self.name.clone());
+        lsa.set_seqno(self.seqno);
+        lsa.set_leaf(self.leaf);
+        lsa.set_links(RepeatedField::from_vec(
+            links
+                .iter()
+                .map(|(name, cost)| {
+                    let mut l = LinkState_Link::new();
+                    l.set_name(name.clone());
+                    l.set_cost(*cost);
+                    l
+                })
+                .collect(),
+        ));
+        lsa.set_subnets(RepeatedField::from_vec(
+            subnets
+                .iter()
+                .map(|(addr, mask)| {
+                    let mut s = LinkState_Subnet::new();
+                    s.set_sub_net(parse_ip(*addr));
+                    s.set_net_mask(u32::from(*mask));
+                    s
+                })
+                .collect(),
+        ));
+
+        self.entries.insert(
+            self.name.clone(),
+            Entry {
+                lsa: lsa.clone(),
+                received: Instant::now(),
+            },
+        );
+        lsa
+    }
+
+    /// my own advertisement at max age, flush it from every node
+    pub fn flush_own(&mut self) -> Option<LinkState> {
+        let mut lsa = self.entries.remove(&self.name)?.lsa;
+        lsa.set_seqno(self.seqno.wrapping_add(1));
+        lsa.set_age(self.max_age);
+        Some(lsa)
+    }
+
+    pub fn receive(&mut self, lsa: LinkState) -> Receive {
+        let origin = lsa.get_origin().to_string();
+        let flush = lsa.age >= self.max_age;
+
+        if origin == self.name {
+            if newer(lsa.seqno, self.seqno) {
+                info!("get my own advertisement {} from last run", lsa.seqno);
+                self.seqno = lsa.seqno;
+                return Receive::Own;
+            }
+            return Receive::Same;
+        }
+
+        let (seqno, age) = match self.entries.get(&origin) {
+            None if flush => return Receive::Same,
+            None => {
+                self.store(lsa);
+                return Receive::Newer;
+            }
+            Some(e) => (e.lsa.seqno, age(e)),
+        };
+        if newer(lsa.seqno, seqno) || (lsa.seqno == seqno && flush && age < self.max_age) {
+            if flush {
+                info!("flush advertisement of {}", origin);
+                self.entries.remove(&origin);
+            } else {
+                self.store(lsa);
+            }
+            Receive::Newer
+        } else if lsa.seqno == seqno {
+            Receive::Same
+        } else {
+            Receive::Older
+        }
+    }
+
+    fn store(&mut self, lsa: LinkState) {
+        debug!("store advertisement {} of {}", lsa.seqno, lsa.get_origin());
+        self.entries.insert(
+            lsa.get_origin().to_string(),
+            Entry {
+                lsa,
+                received: Instant::now(),
+            },
+        );
+    }
+
+    /// the advertisement of `origin`, with the current age
+    pub fn get(&self, origin: &str) -> Option<LinkState> {
+        self.entries.get(origin).map(aged)
+    }
+
+    pub fn all(&self) -> Vec<LinkState> {
+        self.entries.values().map(aged).collect()
+    }
+
+    /// remove the advertisement reach max age, return true if any
+    pub fn expire(&mut self) -> bool {
+        let (name, max_age) = (&self.name, self.max_age);
+        let before = self.entries.len();
+        self.entries.retain(|origin, e| {
+            let keep = origin == name || age(e) < max_age;
+            if !keep {
+                info!("advertisement of {} reach max age", origin);
+            }
+            keep
+        });
+        before != self.entries.len()
+    }
+
+    /// subnets announced by `origin`
+    pub fn subnets(&self, origin: &str) -> Vec<Prefix> {
+        match self.entries.get(origin) {
+            None => vec![],
+            Some(e) => e
+                .lsa
+                .get_subnets()
+                .iter()
+                .filter(|s| s.sub_net.len() == 4 || s.sub_net.len() == 16)
+                .map(|s| (read_ip(&s.sub_net), s.net_mask as u16))
+                .collect(),
+        }
+    }
+
+    fn has_link(&self, from: &str, to: &str) -> bool {
+        self.entries
+            .get(from)
+            .map_or(false, |e| e.lsa.get_links().iter().any(|l| l.name == to))
+    }
+
+    /// dijkstra from myself, ties are broken by name so every node get
+    /// the same result
+    pub fn shortest_paths(&self) -> HashMap<String, Path> {
+        let mut done: HashMap<String, Path> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        heap.push(Reverse((0u32, self.name.clone(), String::new())));
+
+        while let Some(Reverse((cost, name, next))) = heap.pop() {
+            if done.contains_key(&name) {
+                continue;
+            }
+            let links = match self.entries.get(&name) {
+                Some(e) if name == self.name || !e.lsa.leaf => e.lsa.get_links(),
+                _ => &[],
+            };
+            for l in links {
+                if done.contains_key(&l.name) || !self.has_link(&l.name, &name) {
+                    continue;
+                }
+                let next = if name == self.name {
+                    l.name.clone()
+                } else {
+                    next.clone()
+                };
+                heap.push(Reverse((cost.saturating_add(l.cost), l.name.clone(), next)));
+            }
+            done.insert(name, Path { next, cost });
+        }
+
+        done.remove(&self.name);
+        done
+    }
+}
+
+fn age(e: &Entry) -> u32 {
+    e.lsa
+        .age
+        .saturating_add(e.received.elapsed().as_secs() as u32)
+}
+
+fn aged(e: &Entry) -> LinkState {
+    let mut lsa = e.lsa.clone();
+    lsa.set_age(age(e));
+    lsa
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lsa(origin: &str, seqno: u32, links: &[(&str, u32)]) -> LinkState {
+        let mut db = LinkStateDb::new(origin.to_string(), seqno - 1, 100);
+        let links: Vec<(String, u32)> = links.iter().map(|(n, c)| (n.to_string(), *c)).collect();
+        db.originate(&links, &[("128.66.1.0".parse().unwrap(), 24)])
+    }
+
+    #[test]
+    pub fn shortest_path() {
+        // me - a - c is cheaper than me - b - c, d only announce one side
+        let mut db = LinkStateDb::new("me".to_string(), 0, 100);
+        db.originate(&[("a".to_string(), 10), ("b".to_string(), 10)], &[]);
+        assert_eq!(
+            db.receive(lsa("a", 1, &[("me", 10), ("c", 10)])),
+            Receive::Newer
+        );
+        assert_eq!(
+            db.receive(lsa("b", 1, &[("me", 10), ("c", 50)])),
+            Receive::Newer
+        );
+        assert_eq!(
+            db.receive(lsa("c", 1, &[("a", 10), ("b", 50), ("d", 1)])),
+            Receive::Newer
+        );
+        assert_eq!(db.receive(lsa("d", 1, &[])), Receive::Newer);
+
+        let paths = db.shortest_paths();
+        assert_eq!(
+            paths["a"],
+            Path {
+                next: "a".to_string(),
+                cost: 10
+            }
+        );
+        assert_eq!(
+            paths["c"],
+            Path {
+                next: "a".to_string(),
+                cost: 20
+            }
+        );
+        assert!(!paths.contains_key("d"));
+        assert_eq!(db.subnets("c").len(), 1);
+
+        // a leaf is reachable, but never on the path to other node
+        let mut leaf = lsa("a", 2, &[("me", 10), ("c", 10)]);
+        leaf.set_leaf(true);
+        assert_eq!(db.receive(leaf), Receive::Newer);
+        let paths = db.shortest_paths();
+        assert_eq!(paths["a"].cost, 10);
+        assert_eq!(paths["c"].next, "b");
+    }
+
+    #[test]
+    pub fn sequence_and_flush() {
+        let mut db = LinkStateDb::new("me".to_string(), 0, 100);
+        assert_eq!(db.receive(lsa("a", 5, &[])), Receive::Newer);
+        assert_eq!(db.receive(lsa("a", 5, &[])), Receive::Same);
+        assert_eq!(db.receive(lsa("a", 4, &[])), Receive::Older);
+        assert_eq!(db.receive(lsa("me", 7, &[])), Receive::Own);
+
+        let mut flush = lsa("a", 5, &[]);
+        flush.set_age(100);
+        assert_eq!(db.receive(flush.clone()), Receive::Newer);
+        assert!(db.get("a").is_none());
+        assert_eq!(db.receive(flush), Receive::Same);
+    }
+}
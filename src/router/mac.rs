@@ -0,0 +1,84 @@
+use super::peer::Host;
+use crate::internal::frame::{mac_to_string, Mac};
+use log::*;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// learned MAC address, same as a ethernet bridge
+///
+/// a MAC behind the tap device is `Host::Localhost`, a MAC in other node is
+/// the neighbor the frame come from
+#[derive(Debug)]
+pub struct MacTable {
+    entries: HashMap<Mac, (Host, Instant)>,
+    max_age: Duration,
+}
+
+impl MacTable {
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            entries: HashMap::new(),
+            max_age,
+        }
+    }
+
+    /// `mac` is behind `host`, a MAC moved to other host is updated
+    pub fn learn(&mut self, mac: Mac, host: Host) {
+        if mac[0] & 1 == 1 {
+            return;
+        }
+        let now = Instant::now();
+        match self.entries.insert(mac, (host.clone(), now)) {
+            Some((old, _)) if old != host => {
+                info!("{} move from {:?} to {:?}", mac_to_string(&mac), old, host)
+            }
+            Some(_) => {}
+            None => debug!("learn {} at {:?}", mac_to_string(&mac), host),
+        }
+    }
+
+    pub fn get(&self, mac: &Mac) -> Option<Host> {
+        match self.entries.get(mac) {
+            Some((host, seen)) if seen.elapsed() < self.max_age => Some(host.clone()),
+            _ => None,
+        }
+    }
+
+    /// the neighbor at `old` move to `new`
+    pub fn roam(&mut self, old: SocketAddr, new: SocketAddr) {
+        for (host, _) in self.entries.values_mut() {
+            if *host == Host::Socket(old) {
+                *host = Host::Socket(new);
+            }
+        }
+    }
+
+    /// forget the MAC not seen for max age
+    pub fn expire(&mut self) {
+        let max_age = self.max_age;
+        self.entries.retain(|_, (_, seen)| seen.elapsed() < max_age);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn learn_and_move() {
+        let mut t = MacTable::new(Duration::from_secs(300));
+        let a = [2, 0, 0, 0, 0, 1];
+        let addr = "192.0.2.1:1234".parse().unwrap();
+
+        assert_eq!(t.get(&a), None);
+        t.learn(a, Host::Localhost);
+        assert_eq!(t.get(&a), Some(Host::Localhost));
+        t.learn(a, Host::Socket(addr));
+        assert_eq!(t.get(&a), Some(Host::Socket(addr)));
+
+        // a group address is never learned
+        t.learn([0xff; 6], Host::Localhost);
+        assert_eq!(t.get(&[0xff; 6]), None);
+    }
+}
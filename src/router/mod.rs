@@ -1,179 +1,1121 @@
+pub mod cache;
+pub mod dv;
+pub mod keepalive;
+pub mod lpm;
+pub mod ls;
+pub mod mac;
 pub mod peer;
+pub mod punch;
+pub mod relay;
 pub mod table;
 
-pub use self::peer::{Host, Peer};
+use self::cache::{NextHop, RouteCache};
+use self::dv::{Decision, DistanceVector, Prefix, INFINITY};
+use self::keepalive::{Event, Keepalive};
+use self::ls::{LinkStateDb, Path, Receive};
+use self::mac::MacTable;
+pub use self::peer::{Host, Metric, Peer, HOP_COST};
+use self::punch::Punch;
+use self::relay::Bandwidth;
 pub use self::table::{LikeRouter, Table};
-use crate::config::Config;
+use crate::config::{Config, Routing};
+use crate::generated::transport::{Endpoint, Endpoint_Step, LinkState, Node, PingPong};
+use crate::internal::buffer;
+use crate::internal::frame::{self, Frame};
+use crate::internal::icmp;
 use crate::internal::message::Message;
 use crate::internal::package::Package;
-use crate::network::SELF_SHARE;
+use crate::internal::queue;
+use crate::network::SELF_INIT;
 use log::*;
+use std::collections::HashMap;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
-use std::sync::RwLock;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::prelude::stream::Stream;
 use tokio::prelude::{Async, Future};
-use tokio::sync::mpsc;
+use tokio::timer::Interval;
+
+/// a route not updated in this many full update is retracted
+const ROUTE_TIMEOUT_UPDATES: u32 = 4;
+
+/// TTL of a frame read from the tap device
+const FRAME_TTL: u32 = 64;
+/// a MAC not seen in this long is forgot, same as linux bridge
+const MAC_MAX_AGE: Duration = Duration::from_secs(300);
+
+/// ask a relay to introduce a node at most once in it
+const PUNCH_RETRY: Duration = Duration::from_secs(60);
+/// stay behind the relay if punching not finished in it
+const PUNCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// the route tables, shared by the router of every worker
+#[derive(Debug, Clone)]
+pub struct Tables {
+    ipv4: Arc<RwLock<Table>>,
+    ipv6: Arc<RwLock<Table>>,
+    /// bytes forwarded for other node by all workers, one bucket so the
+    /// burst is never smaller than the `relay_rate`
+    relay_limit: Arc<Mutex<Bandwidth>>,
+}
+
+impl Default for Tables {
+    fn default() -> Self {
+        Self {
+            ipv4: Arc::default(),
+            ipv6: Arc::default(),
+            relay_limit: Arc::new(Mutex::new(Bandwidth::new(Config::get().relay_rate * 1024))),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct Router {
-    ipv4_table: RwLock<Table>,
-    ipv6_table: RwLock<Table>,
-    tx: mpsc::UnboundedSender<Message>,
-    rx: mpsc::UnboundedReceiver<Message>,
+    ipv4_table: Arc<RwLock<Table>>,
+    ipv6_table: Arc<RwLock<Table>>,
+    /// next hop of recent destinations, so a package skip the tables
+    cache: RouteCache,
+    /// the router of the first worker, only a shard has it
+    ///
+    /// a shard only forward the package of its device queue and socket, the
+    /// rest is passed to the control plane in the first worker
+    control: Option<queue::Sender<Message>>,
+    /// address finished the handshake, and the name in allow-list
+    authenticated: HashMap<SocketAddr, String>,
+    keepalive: Keepalive,
+    keepalive_timer: Interval,
+    routing: Routing,
+    dv: DistanceVector,
+    ls: LinkStateDb,
+    /// routes installed by the last shortest path first
+    installed: Vec<Prefix>,
+    /// subnets announced by myself
+    local: Vec<Prefix>,
+    icmp_limit: icmp::RateLimit,
+    macs: MacTable,
+    punch: Punch,
+    /// bytes forwarded for other node, shared by every worker
+    relay_limit: Arc<Mutex<Bandwidth>>,
+    /// the counters of the buffer pool in the last full update
+    buffer_stats: buffer::Stats,
+    update_timer: Interval,
+    tx: queue::Sender<Message>,
+    rx: queue::Receiver<Message>,
 }
 
 impl Router {
-    pub fn new(tx: mpsc::UnboundedSender<Message>, rx: mpsc::UnboundedReceiver<Message>) -> Self {
+    pub fn new(tx: queue::Sender<Message>, rx: queue::Receiver<Message>) -> Self {
+        Self::with_tables(tx, rx, Tables::default(), None)
+    }
+
+    /// a router on `tables`, a shard of the router of `control` if given
+    pub fn with_tables(
+        tx: queue::Sender<Message>,
+        rx: queue::Receiver<Message>,
+        tables: Tables,
+        control: Option<queue::Sender<Message>>,
+    ) -> Self {
+        let c = Config::get();
+
+        // start from the clock, so the sequence number is still newer after restart
+        let seqno = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as u32)
+            .unwrap_or(0);
+        let local = c.get_announce();
+        let mut dv = DistanceVector::new(c.name.clone(), seqno);
+        dv.prefer(c.exit_nodes.clone());
+        for prefix in &local {
+            dv.originate(*prefix);
+        }
+        let max_age = c.update_interval as u32 * ROUTE_TIMEOUT_UPDATES;
+        let mut ls = LinkStateDb::new(c.name.clone(), seqno, max_age);
+        ls.set_leaf(!c.relay);
+        let cache = RouteCache::new(&[&tables.ipv4.read().unwrap(), &tables.ipv6.read().unwrap()]);
+
         Router {
             tx,
             rx,
-            ipv6_table: Table::new().into(),
-            ipv4_table: Table::new().into(),
+            ipv6_table: tables.ipv6,
+            ipv4_table: tables.ipv4,
+            cache,
+            control,
+            authenticated: HashMap::new(),
+            keepalive: Keepalive::new(c.keepalive_misses, c.keepalive_misses * 4),
+            keepalive_timer: Interval::new_interval(Duration::from_secs(c.keepalive_interval)),
+            routing: c.routing,
+            dv,
+            ls,
+            installed: vec![],
+            local,
+            icmp_limit: icmp::RateLimit::new(),
+            macs: MacTable::new(MAC_MAX_AGE),
+            punch: Punch::new(PUNCH_RETRY, PUNCH_TIMEOUT),
+            relay_limit: tables.relay_limit,
+            buffer_stats: buffer::stats(),
+            update_timer: Interval::new_interval(Duration::from_secs(c.update_interval)),
         }
     }
 }
 
 impl Future for Router {
     type Item = ();
-    type Error = mpsc::error::UnboundedRecvError;
+    type Error = ();
 
     fn poll(&mut self) -> Result<Async<Self::Item>, Self::Error> {
         loop {
-            match self.rx.poll()? {
-                Async::Ready(Some(m)) => {
+            match self.rx.poll() {
+                Ok(Async::Ready(Some(m))) => {
                     self.router_message(m);
                 }
-                Async::Ready(None) => panic!(),
-                Async::NotReady => break,
+                Ok(Async::NotReady) => break,
+                Ok(Async::Ready(None)) | Err(()) => {
+                    warn!("network is gone, stop the router");
+                    return Ok(Async::Ready(()));
+                }
             };
         }
+
+        // the timers of the control plane
+        if self.control.is_some() {
+            return Ok(Async::NotReady);
+        }
+
+        loop {
+            match self.keepalive_timer.poll() {
+                Ok(Async::Ready(Some(_))) => self.keepalive(),
+                Ok(Async::Ready(None)) => unreachable!("interval never end"),
+                Ok(Async::NotReady) => break,
+                Err(e) => {
+                    error!("keepalive timer error {}", e);
+                    break;
+                }
+            }
+        }
+
+        loop {
+            match self.update_timer.poll() {
+                Ok(Async::Ready(Some(_))) => self.full_update(),
+                Ok(Async::Ready(None)) => unreachable!("interval never end"),
+                Ok(Async::NotReady) => break,
+                Err(e) => {
+                    error!("update timer error {}", e);
+                    break;
+                }
+            }
+        }
         Ok(Async::NotReady)
     }
 }
 
 impl Router {
     pub fn router_message(&mut self, m: Message) {
+        if let Some(control) = &mut self.control {
+            match m {
+                Message::PackageShareRead(_, _) | Message::InterfaceRead(_) => {}
+                m => {
+                    control.send(m);
+                    return;
+                }
+            }
+        }
         match m {
             Message::PackageShareRead(package, ttl) => {
                 trace!("router get PackageShareRead read");
-                match self.find_in_table(&package) {
-                    Some(peer) => match peer.get_host() {
-                        Host::Socket(addr) => {
-                            info!(
-                                "{} -> {} route to real address {}",
-                                package.source_address(),
-                                package.destination_address(),
-                                addr
-                            );
-                            self.tx
-                                .try_send(Message::PackageShareWrite(addr, package, ttl))
-                                .unwrap();
-                        }
-                        Host::Localhost => {
-                            info!(
-                                "{} -> {} route to Self",
-                                package.source_address(),
-                                package.destination_address()
-                            );
-                            self.tx.try_send(Message::InterfaceWrite(package)).unwrap();
+                self.forward(package, ttl, true);
+            }
+            Message::HandshakeRead(addr, name) => {
+                info!("{} at {} is authenticated", name, addr);
+                self.punch.done(&name);
+                self.authenticated.insert(addr, name);
+                match self.routing {
+                    Routing::DistanceVector => self.dump(addr),
+                    Routing::LinkState => {
+                        for lsa in self.ls.all() {
+                            self.tx.send(Message::LinkStateWrite(addr, lsa));
                         }
-                        Host::Unreachable => {
-                            info!(
-                                "{} -> {} can'find edge to reach, drop package",
-                                package.source_address(),
-                                package.destination_address()
-                            );
-                            self.tx.try_send(Message::DoNoting).unwrap();
+                        self.originate();
+                    }
+                }
+            }
+            Message::AddNodeRead(_, _) | Message::DelNodeRead(_, _)
+                if self.routing != Routing::DistanceVector =>
+            {
+                trace!("ignore node message in {:?} mode", self.routing);
+            }
+            Message::AddNodeRead(addr, node) => {
+                let announce = match self.check_node(addr, &node) {
+                    Some(a) => a,
+                    None => return,
+                };
+
+                // hello of a new neighbor, carry no sequence number
+                if node.jump == -1 {
+                    self.dump(addr);
+                    return;
+                }
+                self.update((announce, node.net_mask as u16), addr, &node, node.metric);
+            }
+            Message::DelNodeRead(addr, node) => {
+                trace!("router get DelNode read from {}", addr);
+                let withdraw = match self.check_node(addr, &node) {
+                    Some(a) => a,
+                    None => return,
+                };
+                self.update((withdraw, node.net_mask as u16), addr, &node, INFINITY);
+            }
+            Message::Shutdown if self.routing == Routing::LinkState => {
+                info!("flush my advertisement from all neighbor");
+                if let Some(lsa) = self.ls.flush_own() {
+                    self.flood(lsa, None);
+                }
+                self.tx.send(Message::Shutdown);
+            }
+            Message::Shutdown => {
+                info!("retract myself from all neighbor");
+                self.dv.bump();
+                for prefix in self.dv.prefixes() {
+                    if !self.dv.is_own(&prefix) {
+                        continue;
+                    }
+                    for to in self.neighbors() {
+                        if let Some(mut node) = self.route_node(prefix, to) {
+                            node.set_metric(INFINITY);
+                            self.tx.send(Message::DelNodeWrite(to, node));
                         }
-                    },
-                    None => {
-                        info!(
-                            "{} -> {} not find in router table, drop package",
-                            package.source_address(),
-                            package.destination_address()
-                        );
-                        self.tx.try_send(Message::DoNoting).unwrap();
                     }
                 }
+                self.tx.send(Message::Shutdown);
             }
-            Message::AddNodeRead(addr, mut node) => {
-                if node.name == Config::get().name {
-                    info!("get myself");
+            Message::LinkStateRead(addr, lsa) => {
+                if !self.authenticated.contains_key(&addr) {
+                    warn!("refuse link state from unauthenticated {}", addr);
                     return;
                 }
-
-                if node.jump == -1 {
-                    node.jump = 0;
-                    self.tx
-                        .try_send(Message::AddNodeWrite(addr, SELF_SHARE.clone()))
-                        .unwrap();
+                if self.routing != Routing::LinkState {
+                    trace!("ignore link state in {:?} mode", self.routing);
+                    return;
                 }
-
-                let source = {
-                    if node.real_ip.len() == 0 {
-                        addr
-                    } else {
-                        let ip = read_ip(&node.real_ip);
-                        SocketAddr::new(ip, node.port as u16)
+                let origin = lsa.get_origin().to_string();
+                match self.ls.receive(lsa.clone()) {
+                    Receive::Newer => {
+                        self.flood(lsa, Some(addr));
+                        self.shortest_path_first();
                     }
-                };
-
-                let v = read_ip(&node.sub_net);
-                if let Ok(()) = self.insert_to_table(
-                    v,
-                    node.net_mask as u16,
-                    node.name.clone(),
-                    Host::Socket(source),
-                ) {
-                    let jump = node.get_jump() + 1;
-                    node.set_jump(jump);
-                    node.set_real_ip(parse_ip(source.ip()));
-                    node.set_port(source.port() as i32);
-                    info!("broadcast to all {:?}", node);
-                    for node_addr in self.get_all_node() {
-                        self.tx
-                            .try_send(Message::AddNodeWrite(node_addr, node.clone()))
-                            .unwrap();
+                    Receive::Same => {}
+                    Receive::Older => {
+                        if let Some(newer) = self.ls.get(&origin) {
+                            self.tx.send(Message::LinkStateWrite(addr, newer));
+                        }
                     }
+                    Receive::Own => self.originate(),
                 }
             }
-            Message::DelNodeRead(addr, _) => {
-                trace!("router get DelNode read from {}", addr);
-                self.tx.try_send(Message::DoNoting).unwrap();
+            Message::FrameRead(frame) => {
+                if frame.len() < frame::HEADER_LEN {
+                    debug!("drop short frame from interface");
+                    return;
+                }
+                let origin = Config::get().name.clone();
+                self.switch(None, origin, frame, FRAME_TTL);
+            }
+            Message::FrameShareRead(addr, origin, frame, ttl) => {
+                if !self.authenticated.contains_key(&addr) {
+                    warn!("refuse frame from unauthenticated {}", addr);
+                    return;
+                }
+                if frame.len() < frame::HEADER_LEN {
+                    debug!("drop short frame from {}", addr);
+                    return;
+                }
+                self.switch(Some(addr), origin, frame, ttl);
             }
+            Message::EndpointRead(addr, endpoint) => self.endpoint(addr, endpoint),
+            Message::Roamed(old, new) => self.roamed(old, new),
+            Message::Reload => self.reload(),
             Message::InterfaceRead(package) => {
                 trace!("router get interface read");
-                self.router_message(Message::PackageShareRead(package, 127));
+                let ttl = u32::from(package.ttl());
+                self.forward(package, ttl, false);
             }
             Message::DoNoting => {
-                self.tx.try_send(Message::DoNoting).unwrap();
+                self.tx.send(Message::DoNoting);
             }
-            Message::PingPongRead(addr, _name) => {
-                info!("get PingPongRead from {}", addr);
-                self.tx
-                    .try_send(Message::PingPongWrite(addr, Config::get().name.clone()))
-                    .unwrap();
+            Message::PingPongRead(addr, ping) => {
+                trace!("get PingPongRead {} from {}", ping.sequence, addr);
+                let event = if ping.reply {
+                    self.keepalive.pong(addr, ping.sequence)
+                } else {
+                    let mut pong = PingPong::new();
+                    pong.set_name(Config::get().name.clone());
+                    pong.set_sequence(ping.sequence);
+                    pong.set_reply(true);
+                    self.tx.send(Message::PingPongWrite(addr, pong));
+                    self.keepalive.seen(addr)
+                };
+                if let Some(e) = event {
+                    self.liveness_changed(e);
+                }
+                self.update_cost(addr);
             }
             Message::InterfaceWrite(_) => panic!("InterfaceWrite can not route"),
+            Message::FrameWrite(_) => panic!("FrameWrite can not route"),
+            Message::FrameShareWrite(_, _, _, _) => panic!("FrameShareWrite can not route"),
             Message::PingPongWrite(_, _) => panic!("PingPongWrite can not route"),
             Message::AddNodeWrite(_, _) => panic!("AddNodeWrite can not route"),
             Message::PackageShareWrite(_, _, _) => panic!("PackageShareWrite can not route"),
             Message::DelNodeWrite(_, _) => panic!("DelNodeWrite can not route"),
+            Message::LinkStateWrite(_, _) => panic!("LinkStateWrite can not route"),
+            Message::EndpointWrite(_, _) => panic!("EndpointWrite can not route"),
         }
     }
 }
 
 impl Router {
+    /// ping every real address in the table, apply the result of last round
+    fn keepalive(&mut self) {
+        self.macs.expire();
+        self.traverse();
+
+        let mut addrs = self.ipv4_table.read().unwrap().get_all_sockets();
+        addrs.extend(self.ipv6_table.read().unwrap().get_all_sockets());
+        addrs.sort();
+        addrs.dedup();
+
+        let (pings, events) = self.keepalive.tick(&addrs);
+        for e in events {
+            self.liveness_changed(e);
+        }
+        for addr in &addrs {
+            self.update_cost(*addr);
+        }
+        for (addr, sequence) in pings {
+            let mut ping = PingPong::new();
+            ping.set_name(Config::get().name.clone());
+            ping.set_sequence(sequence);
+            self.tx.send(Message::PingPongWrite(addr, ping));
+        }
+    }
+
+    /// apply the measured link cost of `addr` to the table,
+    /// the best host of a peer may change
+    fn update_cost(&mut self, addr: SocketAddr) {
+        let cost = match self.keepalive.get(&addr).and_then(|l| l.cost()) {
+            Some(c) => c,
+            None => return,
+        };
+        let changed = self.ipv4_table.write().unwrap().set_link_cost(addr, cost)
+            | self.ipv6_table.write().unwrap().set_link_cost(addr, cost);
+        if changed {
+            debug!("link cost of {} is {}", addr, cost);
+        }
+    }
+
+    /// link cost to a neighbor, the default one if not measured yet
+    fn link_cost(&self, addr: SocketAddr) -> u32 {
+        self.keepalive
+            .get(&addr)
+            .and_then(|l| l.cost())
+            .unwrap_or_else(|| Metric::default().link)
+    }
+
+    /// apply a update of `prefix` from the neighbor `addr`,
+    /// `metric` is the one it announced, `INFINITY` for a retraction
+    fn update(&mut self, prefix: Prefix, addr: SocketAddr, node: &Node, metric: u32) {
+        let route = Metric::routed(metric, self.link_cost(addr), 1);
+        let total = if metric == INFINITY {
+            INFINITY
+        } else {
+            route.total()
+        };
+        let before = self.best_route(prefix);
+        let host = match self.authenticated.get(&addr) {
+            Some(relay) if *relay != node.name => Host::Relay(addr, relay.clone()),
+            _ => Host::Socket(addr),
+        };
+
+        let decision = self
+            .dv
+            .decide(prefix, &node.name, node.seqno, total, node.jump.max(0) + 1);
+        debug!(
+            "{:?} seqno {} metric {} from {}: {:?}",
+            prefix, node.seqno, total, addr, decision
+        );
+        match decision {
+            Decision::Replace => {
+                let _ = self.remove_prefix(prefix);
+                self.insert_to_table(prefix.0, prefix.1, node.name.clone(), host, route)
+                    .unwrap();
+            }
+            Decision::Update => {
+                let _ = self.insert_to_table(prefix.0, prefix.1, node.name.clone(), host, route);
+            }
+            Decision::Drop => {
+                if let Ok(()) = self.delete_from_table(prefix.0, prefix.1, &host) {
+                    if self.best_route(prefix).is_none() {
+                        self.dv.retract(&prefix);
+                    }
+                }
+            }
+            Decision::Retract => {
+                let _ = self.remove_prefix(prefix);
+            }
+            Decision::Ignore => return,
+        }
+
+        if self.best_route(prefix) != before {
+            self.advertise(prefix);
+        }
+    }
+
+    /// route a package from the interface, or from a neighbor if `transit`
+    fn forward(&mut self, mut package: Package, ttl: u32, transit: bool) {
+        let c = Config::get();
+        match self.next_hop(package.destination_address()) {
+            Some(hop) => match hop {
+                NextHop::Socket(addr) => {
+                    if transit && !c.relay {
+                        info!(
+                            "{} -> {} not a relay, drop package",
+                            package.source_address(),
+                            package.destination_address()
+                        );
+                        self.icmp_error(&package, icmp::net_unreachable);
+                        return;
+                    }
+                    if transit && !self.relay_limit.lock().unwrap().allow(package.len()) {
+                        debug!(
+                            "{} -> {} over the relay limit, drop package",
+                            package.source_address(),
+                            package.destination_address()
+                        );
+                        return;
+                    }
+                    if ttl <= 1 {
+                        info!(
+                            "{} -> {} TTL exceeded, drop package",
+                            package.source_address(),
+                            package.destination_address()
+                        );
+                        self.icmp_error(&package, icmp::time_exceeded);
+                        return;
+                    }
+                    info!(
+                        "{} -> {} route to real address {}",
+                        package.source_address(),
+                        package.destination_address(),
+                        addr
+                    );
+                    // every overlay jump is one hop, the IP TTL follow it
+                    // so traceroute see the jumps
+                    let ttl = ttl.min(256) - 1;
+                    package.set_ttl(ttl as u8);
+                    self.tx.send(Message::PackageShareWrite(addr, package, ttl));
+                }
+                NextHop::Localhost => {
+                    info!(
+                        "{} -> {} route to Self",
+                        package.source_address(),
+                        package.destination_address()
+                    );
+                    self.tx.send(Message::InterfaceWrite(package));
+                }
+                NextHop::Unreachable => {
+                    info!(
+                        "{} -> {} can'find edge to reach, drop package",
+                        package.source_address(),
+                        package.destination_address()
+                    );
+                    self.icmp_error(&package, icmp::net_unreachable);
+                }
+            },
+            None => {
+                info!(
+                    "{} -> {} not find in router table, drop package",
+                    package.source_address(),
+                    package.destination_address()
+                );
+                self.icmp_error(&package, icmp::net_unreachable);
+            }
+        }
+    }
+
+    /// the selected next hop of `prefix` and its metric
+    fn best_route(&self, prefix: Prefix) -> Option<(Host, Option<Metric>)> {
+        let table = match prefix.0 {
+            IpAddr::V4(_) => self.ipv4_table.read().unwrap(),
+            IpAddr::V6(_) => self.ipv6_table.read().unwrap(),
+        };
+        table
+            .get(prefix.0, prefix.1)
+            .map(|p| (p.get_host(), p.get_metric()))
+    }
+
+    /// the update of `prefix` sent to neighbor `to`
+    ///
+    /// split horizon with poison reverse, a route is announced as unreachable
+    /// to its own next hop
+    ///
+    /// a node not a relay only announce its own prefix
+    fn route_node(&mut self, prefix: Prefix, to: SocketAddr) -> Option<Node> {
+        if !Config::get().relay && !self.dv.is_own(&prefix) {
+            return None;
+        }
+        let metric = match self.best_route(prefix) {
+            Some((Host::Localhost, _)) => 0,
+            Some((h, _)) if h.socket() == Some(to) => INFINITY,
+            Some((Host::Socket(_), Some(m))) | Some((Host::Relay(_, _), Some(m))) => m.total(),
+            _ => INFINITY,
+        };
+        self.dv.advertised(&prefix, metric);
+
+        let source = self.dv.get(&prefix)?;
+        let mut node = Node::new();
+        node.set_sub_net(parse_ip(prefix.0));
+        node.set_net_mask(u32::from(prefix.1));
+        node.set_name(source.origin.clone());
+        node.set_jump(source.jump);
+        node.set_seqno(source.seqno);
+        node.set_metric(metric);
+        Some(node)
+    }
+
+    fn neighbors(&self) -> Vec<SocketAddr> {
+        self.authenticated.keys().cloned().collect()
+    }
+
+    /// triggered update, send the route of `prefix` to every neighbor
+    fn advertise(&mut self, prefix: Prefix) {
+        for to in self.neighbors() {
+            if let Some(node) = self.route_node(prefix, to) {
+                trace!("advertise {:?} to {}", node, to);
+                self.tx.send(Message::AddNodeWrite(to, node));
+            }
+        }
+    }
+
+    /// send every route to the neighbor `to`
+    fn dump(&mut self, to: SocketAddr) {
+        for prefix in self.dv.prefixes() {
+            if let Some(node) = self.route_node(prefix, to) {
+                self.tx.send(Message::AddNodeWrite(to, node));
+            }
+        }
+    }
+
+    /// bump my sequence number, retract the stale route,
+    /// then send the full table to every neighbor
+    fn full_update(&mut self) {
+        let stats = buffer::stats();
+        if stats != self.buffer_stats {
+            warn!("{}", stats);
+            self.buffer_stats = stats;
+        }
+        for q in queue::gauges() {
+            debug!(
+                "{} queue depth {}, dropped {}",
+                q.name,
+                q.depth(),
+                q.dropped()
+            );
+        }
+
+        if self.routing == Routing::LinkState {
+            if self.ls.expire() {
+                self.shortest_path_first();
+            }
+            self.originate();
+            return;
+        }
+
+        self.dv.bump();
+        let timeout = Duration::from_secs(Config::get().update_interval) * ROUTE_TIMEOUT_UPDATES;
+        for prefix in self.dv.expire(timeout) {
+            let _ = self.remove_prefix(prefix);
+        }
+        for to in self.neighbors() {
+            self.dump(to);
+        }
+    }
+
+    /// a next hop is gone, retract the route have no next hop left
+    fn retract_lost(&mut self) {
+        for prefix in self.dv.prefixes() {
+            if self.best_route(prefix).is_none() {
+                self.dv.retract(&prefix);
+            }
+        }
+    }
+
+    fn liveness_changed(&mut self, e: Event) {
+        match e {
+            Event::Down(addr) | Event::Up(addr) => {
+                let reachable = e == Event::Up(addr);
+                self.ipv4_table
+                    .write()
+                    .unwrap()
+                    .set_reachable(addr, reachable);
+                self.ipv6_table
+                    .write()
+                    .unwrap()
+                    .set_reachable(addr, reachable);
+            }
+            Event::Dead(addr) => {
+                let n = self.ipv4_table.write().unwrap().remove_socket(addr)
+                    + self.ipv6_table.write().unwrap().remove_socket(addr);
+                info!("{} is dead, remove from {} peer", addr, n);
+                self.retract_lost();
+            }
+        }
+        match self.routing {
+            Routing::DistanceVector => {
+                for to in self.neighbors() {
+                    self.dump(to);
+                }
+            }
+            Routing::LinkState => self.originate(),
+        }
+    }
+
+    /// forward a frame like a ethernet bridge, every neighbor is a port,
+    /// `from` is `None` for a frame of the tap device
+    ///
+    /// broadcast, multicast and unknown unicast are flooded, a flooded frame
+    /// is only accepted from the next hop to its origin, so it never loop
+    fn switch(&mut self, from: Option<SocketAddr>, origin: String, frame: Frame, ttl: u32) {
+        let dest = if frame.is_group() {
+            None
+        } else {
+            self.macs.get(&frame.destination())
+        };
+        if let (None, Some(addr)) = (&dest, from) {
+            if !self.reverse_path(&origin, addr) {
+                trace!("drop {} from {}, not the path to {}", frame, addr, origin);
+                return;
+            }
+        }
+        let host = from.map_or(Host::Localhost, Host::Socket);
+        self.macs.learn(frame.source(), host);
+
+        match dest {
+            Some(Host::Localhost) => {
+                if from.is_some() {
+                    self.tx.send(Message::FrameWrite(frame));
+                }
+            }
+            Some(Host::Socket(next)) | Some(Host::Relay(next, _)) => {
+                if Some(next) == from || ttl <= 1 {
+                    trace!("drop {} to {}", frame, next);
+                    return;
+                }
+                self.tx
+                    .send(Message::FrameShareWrite(next, origin, frame, ttl - 1));
+            }
+            Some(Host::Unreachable) | None => {
+                if ttl > 1 {
+                    for to in self.flood_targets(&origin, from) {
+                        self.tx.send(Message::FrameShareWrite(
+                            to,
+                            origin.clone(),
+                            frame.clone(),
+                            ttl - 1,
+                        ));
+                    }
+                }
+                if from.is_some() {
+                    self.tx.send(Message::FrameWrite(frame));
+                }
+            }
+        }
+    }
+
+    /// `addr` is the neighbor to reach `origin`
+    fn reverse_path(&self, origin: &str, addr: SocketAddr) -> bool {
+        if self.authenticated.get(&addr).map_or(false, |n| n == origin) {
+            return true;
+        }
+        self.get_by_name(origin)
+            .and_then(|p| p.get_host().socket())
+            .map_or(false, |a| a == addr)
+    }
+
+    /// one address of every neighbor, except the origin and the sender
+    fn flood_targets(&self, origin: &str, from: Option<SocketAddr>) -> Vec<SocketAddr> {
+        let sender = from.and_then(|a| self.authenticated.get(&a));
+        let mut names: Vec<&String> = self
+            .authenticated
+            .values()
+            .filter(|n| *n != origin && Some(*n) != sender)
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+            .into_iter()
+            .filter_map(|n| self.neighbor_addr(n))
+            .collect()
+    }
+
+    /// send a ICMP error build by `f` back to the source of `package`,
+    /// it is routed like other package, rate limited per source
+    fn icmp_error(&mut self, package: &Package, f: fn(&Package, IpAddr) -> Option<Package>) {
+        let error = self
+            .local_address(package.destination_address())
+            .and_then(|source| f(package, source));
+        let error = match error {
+            Some(e) => e,
+            None => {
+                self.tx.send(Message::DoNoting);
+                return;
+            }
+        };
+        if !self.icmp_limit.allow(package.source_address()) {
+            debug!("too many ICMP error to {}", package.source_address());
+            self.tx.send(Message::DoNoting);
+            return;
+        }
+        // it is from myself, not a transit package of the relay
+        let ttl = u32::from(error.ttl());
+        self.forward(error, ttl, false);
+    }
+
+    /// my address in the overlay of the same family as `addr`
+    fn local_address(&self, addr: IpAddr) -> Option<IpAddr> {
+        let c = Config::get();
+        match addr {
+            IpAddr::V4(_) => Some(c.get_v4().into()),
+            IpAddr::V6(_) => c.get_v6().first().map(|(a, _)| (*a).into()),
+        }
+    }
+
+    /// read `advertise` again, announce the new subnets and withdraw the
+    /// removed one
+    fn reload(&mut self) {
+        let wanted = match Config::reload() {
+            Ok(c) => c.get_announce(),
+            Err(e) => {
+                error!("can not reload config, {}", e);
+                return;
+            }
+        };
+        let c = Config::get();
+
+        let (removed, added) = changed_prefixes(&self.local, &wanted);
+        if removed.is_empty() && added.is_empty() {
+            info!("nothing to reload");
+            return;
+        }
+        self.local = wanted;
+
+        for prefix in &removed {
+            info!("withdraw {}/{}", prefix.0, prefix.1);
+            let _ = self.remove_prefix(*prefix);
+        }
+        for prefix in &added {
+            info!("announce {}/{}", prefix.0, prefix.1);
+            let _ = self.remove_prefix(*prefix);
+            let result = self.insert_to_table(
+                prefix.0,
+                prefix.1,
+                c.name.clone(),
+                Host::Localhost,
+                Metric::new(0, 0),
+            );
+            if result.is_err() {
+                warn!("{}/{} is already in the table", prefix.0, prefix.1);
+            }
+            self.dv.originate(*prefix);
+        }
+
+        match self.routing {
+            Routing::DistanceVector => {
+                // a newer sequence number, so the retraction replace every route
+                self.dv.bump();
+                for prefix in removed {
+                    for to in self.neighbors() {
+                        if let Some(node) = self.route_node(prefix, to) {
+                            self.tx.send(Message::DelNodeWrite(to, node));
+                        }
+                    }
+                    self.dv.withdraw(&prefix);
+                }
+                for prefix in added {
+                    self.advertise(prefix);
+                }
+            }
+            Routing::LinkState => self.originate(),
+        }
+    }
+
+    /// originate my advertisement with the current neighbors, flood it and
+    /// compute the routes again
+    fn originate(&mut self) {
+        let mut links: HashMap<String, u32> = HashMap::new();
+        for (addr, name) in &self.authenticated {
+            if self.keepalive.get(addr).map_or(false, |l| l.down) {
+                continue;
+            }
+            let cost = self.link_cost(*addr).saturating_add(HOP_COST);
+            let c = links.entry(name.clone()).or_insert(cost);
+            *c = (*c).min(cost);
+        }
+        let mut links: Vec<(String, u32)> = links.into_iter().collect();
+        links.sort();
+
+        let lsa = self.ls.originate(&links, &self.local);
+        self.flood(lsa, None);
+        self.shortest_path_first();
+    }
+
+    /// send `lsa` to every neighbor except the one it come from
+    fn flood(&mut self, lsa: LinkState, from: Option<SocketAddr>) {
+        for to in self.neighbors() {
+            if Some(to) != from {
+                self.tx.send(Message::LinkStateWrite(to, lsa.clone()));
+            }
+        }
+    }
+
+    /// the best address of the neighbor `name`
+    fn neighbor_addr(&self, name: &str) -> Option<SocketAddr> {
+        self.authenticated
+            .iter()
+            .filter(|(a, n)| *n == name && !self.keepalive.get(a).map_or(false, |l| l.down))
+            .map(|(a, _)| *a)
+            .min_by_key(|a| self.link_cost(*a))
+    }
+
+    /// run dijkstra and replace the routes installed last time
+    fn shortest_path_first(&mut self) {
+        for prefix in std::mem::take(&mut self.installed) {
+            let _ = self.remove_prefix(prefix);
+        }
+
+        let c = Config::get();
+        let paths = self.ls.shortest_paths();
+        // only the most preferred reachable exit node is used
+        let exit = paths
+            .keys()
+            .filter(|n| self.ls.subnets(n).iter().any(|p| p.1 == 0))
+            .map(|n| c.exit_rank(n))
+            .min();
+        let origins = select_origins(&self.ls, &paths, |name, prefix| {
+            if self.neighbor_addr(&paths[name].next).is_none() {
+                return false;
+            }
+            if !c.allow_announce(name, prefix.0, prefix.1.into()) {
+                warn!("refuse {:?} of {}, not in allow-list", prefix, name);
+                return false;
+            }
+            if prefix.1 == 0 && (!c.accept_exit(name, 0) || Some(c.exit_rank(name)) != exit) {
+                return false;
+            }
+            // never replace a local subnet
+            !self.local.contains(&prefix)
+        });
+        for (prefix, name) in origins {
+            let path = &paths[&name];
+            let addr = match self.neighbor_addr(&path.next) {
+                Some(a) => a,
+                None => continue,
+            };
+            let link = self.link_cost(addr);
+            let metric = Metric::routed(
+                path.cost.saturating_sub(link.saturating_add(HOP_COST)),
+                link,
+                1,
+            );
+            debug!(
+                "{:?} of {} via {} cost {}",
+                prefix, name, path.next, path.cost
+            );
+            let host = if path.next == name {
+                Host::Socket(addr)
+            } else {
+                Host::Relay(addr, path.next.clone())
+            };
+            if self
+                .insert_to_table(prefix.0, prefix.1, name, host, metric)
+                .is_err()
+            {
+                warn!("{:?} is already in the table", prefix);
+                continue;
+            }
+            self.installed.push(prefix);
+        }
+    }
+
+    /// the neighbor at `old` send from `new` now, every route through `old`
+    /// follow it and `old` is forgot
+    fn roamed(&mut self, old: SocketAddr, new: SocketAddr) {
+        let name = match self.authenticated.remove(&old) {
+            Some(name) => name,
+            None => return,
+        };
+        let n = self.ipv4_table.write().unwrap().replace_socket(old, new)
+            + self.ipv6_table.write().unwrap().replace_socket(old, new);
+        info!("{} roam from {} to {}, move {} peer", name, old, new, n);
+        self.macs.roam(old, new);
+        self.authenticated.insert(new, name);
+    }
+
+    /// node reachable only through a relay, and the relay next to me
+    fn relayed(&self) -> Vec<(String, SocketAddr)> {
+        let me = &Config::get().name;
+        let mut v: Vec<(String, SocketAddr)> = vec![];
+        match self.routing {
+            Routing::DistanceVector => {
+                for prefix in self.dv.prefixes() {
+                    let origin = match self.dv.get(&prefix) {
+                        Some(s) if s.origin != *me && s.retracted.is_none() => &s.origin,
+                        _ => continue,
+                    };
+                    if let Some(next) = self.best_route(prefix).and_then(|(h, _)| h.socket()) {
+                        v.push((origin.clone(), next));
+                    }
+                }
+            }
+            Routing::LinkState => {
+                for (name, path) in self.ls.shortest_paths() {
+                    if let Some(next) = self.neighbor_addr(&path.next) {
+                        v.push((name, next));
+                    }
+                }
+            }
+        }
+        v.sort();
+        v.dedup_by(|a, b| a.0 == b.0);
+        v.retain(|(name, _)| self.neighbor_addr(name).is_none());
+        v
+    }
+
+    /// ask the relay to introduce every node behind it, and punch again the
+    /// node introduced
+    fn traverse(&mut self) {
+        for addr in self.punch.tick() {
+            self.tx.send(Message::AddNodeWrite(addr, SELF_INIT.clone()));
+        }
+        for (name, relay) in self.relayed() {
+            if !self.punch.should_request(&name) {
+                continue;
+            }
+            debug!("ask {} to introduce {}", relay, name);
+            let mut request = Endpoint::new();
+            request.set_step(Endpoint_Step::REQUEST);
+            request.set_name(name);
+            self.tx.send(Message::EndpointWrite(relay, request));
+        }
+    }
+
+    /// a introduction request to me as the relay, or a introduction from the relay
+    fn endpoint(&mut self, addr: SocketAddr, endpoint: Endpoint) {
+        let from = match self.authenticated.get(&addr) {
+            Some(name) => name.clone(),
+            None => {
+                warn!("refuse endpoint from unauthenticated {}", addr);
+                return;
+            }
+        };
+        let c = Config::get();
+        let name = endpoint.get_name();
+        if name == c.name || name == from {
+            return;
+        }
+
+        match endpoint.get_step() {
+            Endpoint_Step::REQUEST => {
+                let target = match self.neighbor_addr(name) {
+                    Some(a) => a,
+                    None => {
+                        debug!("{} ask for {}, not a neighbor", from, name);
+                        return;
+                    }
+                };
+                info!("introduce {} at {} and {} at {}", from, addr, name, target);
+                for &(to, peer, at) in &[(addr, name, target), (target, from.as_str(), addr)] {
+                    let mut punch = Endpoint::new();
+                    punch.set_step(Endpoint_Step::PUNCH);
+                    punch.set_name(peer.to_string());
+                    punch.set_ip(parse_ip(at.ip()));
+                    punch.set_port(u32::from(at.port()));
+                    self.tx.send(Message::EndpointWrite(to, punch));
+                }
+            }
+            Endpoint_Step::PUNCH => {
+                if !c.peers.iter().any(|p| p.name == name) {
+                    warn!("refuse to punch {}, not in allow-list", name);
+                    return;
+                }
+                if self.neighbor_addr(name).is_some() {
+                    return;
+                }
+                let ip = endpoint.get_ip();
+                if (ip.len() != 4 && ip.len() != 16) || endpoint.port > 0xffff {
+                    warn!("bad endpoint of {} from {}", name, from);
+                    return;
+                }
+                let at = SocketAddr::new(read_ip(ip), endpoint.port as u16);
+                self.punch.start(name.to_string(), at);
+                self.tx.send(Message::AddNodeWrite(at, SELF_INIT.clone()));
+            }
+        }
+    }
+
+    /// check a AddNode or DelNode, return the announced subnet if it can be accepted
+    fn check_node(&self, addr: SocketAddr, node: &Node) -> Option<IpAddr> {
+        let name = match self.authenticated.get(&addr) {
+            Some(name) => name,
+            None => {
+                warn!("refuse node message from unauthenticated {}", addr);
+                return None;
+            }
+        };
+
+        if node.name == Config::get().name {
+            info!("get myself");
+            return None;
+        }
+
+        if node.sub_net.len() != 4 && node.sub_net.len() != 16 {
+            warn!(
+                "refuse subnet of {} bytes from {}",
+                node.sub_net.len(),
+                addr
+            );
+            return None;
+        }
+        let announce = read_ip(&node.sub_net);
+        if !Config::get().allow_announce(&node.name, announce, node.net_mask) {
+            warn!(
+                "refuse {}/{} from {}, not in allow-list of {}",
+                announce, node.net_mask, addr, node.name
+            );
+            return None;
+        }
+
+        if !Config::get().accept_exit(&node.name, node.net_mask) {
+            debug!(
+                "ignore default route of {}, not a exit node to use",
+                node.name
+            );
+            return None;
+        }
+
+        // a direct announce must come from the node itself
+        if node.jump <= 0 && *name != node.name {
+            warn!("refuse {} at {} announce as {}", name, addr, node.name);
+            return None;
+        }
+
+        Some(announce)
+    }
+
     pub fn get_all_node(&self) -> Vec<SocketAddr> {
         let v = self.ipv4_table.read().unwrap().get_all_peer();
         let o = &mut self.ipv6_table.read().unwrap().get_all_peer();
 
         v.iter()
             .chain(o.iter())
-            .map(|p| match p.get_host() {
-                Host::Socket(addr) => Some(addr),
-                Host::Unreachable | Host::Localhost => None,
-            })
+            .map(|p| p.get_host().socket())
             .filter(|x| !x.is_none())
             .map(|x| x.unwrap())
             .collect()
@@ -185,10 +1127,35 @@ impl Router {
         mask: u16,
         name: String,
         host: Host,
+        metric: Metric,
     ) -> Result<(), ()> {
         info!(
-            "add {}/{} -> {}:\"{:?}\" to router table",
-            dest, mask, name, host
+            "add {}/{} -> {}:\"{:?}\" {:?} to router table",
+            dest, mask, name, host, metric
+        );
+
+        match dest {
+            IpAddr::V4(v4_addr) => self.ipv4_table.write().unwrap().insert_with_metric(
+                v4_addr.into(),
+                mask,
+                name,
+                host,
+                metric,
+            ),
+            IpAddr::V6(v6_addr) => self.ipv6_table.write().unwrap().insert_with_metric(
+                v6_addr.into(),
+                mask,
+                name,
+                host,
+                metric,
+            ),
+        }
+    }
+
+    pub fn delete_from_table(&self, dest: IpAddr, mask: u16, host: &Host) -> Result<(), ()> {
+        info!(
+            "remove {}/{} -> \"{:?}\" from router table",
+            dest, mask, host
         );
 
         match dest {
@@ -196,17 +1163,44 @@ impl Router {
                 self.ipv4_table
                     .write()
                     .unwrap()
-                    .insert(v4_addr.into(), mask, name, host)
+                    .remove_host(v4_addr.into(), mask, host)
             }
             IpAddr::V6(v6_addr) => {
                 self.ipv6_table
                     .write()
                     .unwrap()
-                    .insert(v6_addr.into(), mask, name, host)
+                    .remove_host(v6_addr.into(), mask, host)
             }
         }
     }
 
+    pub fn remove_prefix(&self, prefix: Prefix) -> Result<(), ()> {
+        info!("remove {}/{} from router table", prefix.0, prefix.1);
+        match prefix.0 {
+            IpAddr::V4(v4_addr) => self
+                .ipv4_table
+                .write()
+                .unwrap()
+                .delete(v4_addr.into(), prefix.1),
+            IpAddr::V6(v6_addr) => self
+                .ipv6_table
+                .write()
+                .unwrap()
+                .delete(v6_addr.into(), prefix.1),
+        }
+    }
+
+    /// the next hop of `dest` from the cache, or the table on a miss
+    fn next_hop(&mut self, dest: IpAddr) -> Option<NextHop> {
+        if let Some(hop) = self.cache.get(dest) {
+            return Some(hop);
+        }
+        match dest {
+            IpAddr::V4(_) => self.cache.resolve(dest, &self.ipv4_table.read().unwrap()),
+            IpAddr::V6(_) => self.cache.resolve(dest, &self.ipv6_table.read().unwrap()),
+        }
+    }
+
     pub fn find_in_table(&self, package: &Package) -> Option<Peer> {
         let dest = package.destination_address();
         match dest {
@@ -247,9 +1241,140 @@ fn read_ip(v: &[u8]) -> IpAddr {
     }
 }
 
+/// the prefixes in `local` but not `wanted`, and the ones in `wanted` but
+/// not `local`
+fn changed_prefixes(local: &[Prefix], wanted: &[Prefix]) -> (Vec<Prefix>, Vec<Prefix>) {
+    let removed = local
+        .iter()
+        .filter(|p| !wanted.contains(p))
+        .cloned()
+        .collect();
+    let added = wanted
+        .iter()
+        .filter(|p| !local.contains(p))
+        .cloned()
+        .collect();
+    (removed, added)
+}
+
+/// the origin of every prefix in the shortest paths `accept`, a prefix
+/// announced by more than one node or twice by one is from the cheapest
+/// path then the smallest name, so every run pick the same one
+fn select_origins<F>(
+    ls: &LinkStateDb,
+    paths: &HashMap<String, Path>,
+    accept: F,
+) -> Vec<(Prefix, String)>
+where
+    F: Fn(&str, Prefix) -> bool,
+{
+    let mut best: HashMap<Prefix, (u32, &str)> = HashMap::new();
+    for (name, path) in paths {
+        for prefix in ls.subnets(name) {
+            match best.get(&prefix) {
+                Some(&(cost, origin)) if (cost, origin) <= (path.cost, name.as_str()) => continue,
+                _ => {}
+            }
+            if accept(name, prefix) {
+                best.insert(prefix, (path.cost, name));
+            }
+        }
+    }
+    let mut v: Vec<(Prefix, String)> = best
+        .into_iter()
+        .map(|(prefix, (_, name))| (prefix, name.to_string()))
+        .collect();
+    v.sort();
+    v
+}
+
 fn parse_ip(v: IpAddr) -> Vec<u8> {
     match v {
         IpAddr::V4(v4) => v4.octets().to_vec(),
         IpAddr::V6(v6) => v6.octets().to_vec(),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn reload_exit_node() {
+        let mut c = Config {
+            exit_node: true,
+            ..Config::default()
+        };
+        let local = c.get_announce();
+
+        // the default routes of a exit node is kept
+        let (removed, added) = changed_prefixes(&local, &c.get_announce());
+        assert!(removed.is_empty());
+        assert!(added.is_empty());
+
+        c.advertise.push("10.1.0.0/16".to_string());
+        let (removed, added) = changed_prefixes(&local, &c.get_announce());
+        assert!(removed.is_empty());
+        assert_eq!(added, vec![("10.1.0.0".parse().unwrap(), 16)]);
+
+        c.exit_node = false;
+        let (removed, _) = changed_prefixes(&local, &c.get_announce());
+        assert_eq!(removed.len(), 2);
+        assert!(removed.iter().all(|p| p.1 == 0));
+    }
+
+    #[test]
+    pub fn announce_once() {
+        let mut c = Config {
+            exit_node: true,
+            ..Config::default()
+        };
+        c.advertise.push("0.0.0.0/0".to_string());
+        assert!(c.check().is_ok());
+        assert_eq!(c.get_announce().iter().filter(|p| p.1 == 0).count(), 2);
+
+        c.advertise.push(c.subnet.clone());
+        assert!(c.check().is_err());
+        assert_eq!(c.get_announce().len(), 3);
+    }
+
+    #[test]
+    pub fn two_exits_behind_relay() {
+        let default: Prefix = ("0.0.0.0".parse().unwrap(), 0);
+        let subnet: Prefix = ("10.2.0.0".parse().unwrap(), 16);
+        let exit = |name: &str, seqno: u32, subnets: &[Prefix]| {
+            let mut db = LinkStateDb::new(name.to_string(), seqno, 100);
+            db.originate(&[("relay".to_string(), 10)], subnets)
+        };
+
+        let mut ls = LinkStateDb::new("me".to_string(), 0, 100);
+        ls.originate(&[("relay".to_string(), 10)], &[]);
+        let mut relay = LinkStateDb::new("relay".to_string(), 0, 100);
+        let links: Vec<(String, u32)> = ["me", "b", "a"]
+            .iter()
+            .map(|n| (n.to_string(), 10))
+            .collect();
+        ls.receive(relay.originate(&links, &[]));
+        // both is reached through the relay at the same cost, and b list
+        // a prefix twice
+        ls.receive(exit("b", 0, &[default, subnet, subnet]));
+        ls.receive(exit("a", 0, &[default]));
+
+        let paths = ls.shortest_paths();
+        let origins = select_origins(&ls, &paths, |_, _| true);
+        assert_eq!(
+            origins,
+            vec![(default, "a".to_string()), (subnet, "b".to_string())]
+        );
+
+        let mut table = Table::new();
+        for (prefix, name) in origins {
+            let host = Host::Relay("192.0.2.1:1234".parse().unwrap(), "relay".to_string());
+            table.insert(prefix.0, prefix.1, name, host).unwrap();
+        }
+
+        // a refused origin leave the prefix to the other one
+        let origins = select_origins(&ls, &paths, |name, _| name != "a");
+        assert_eq!(origins[0], (default, "b".to_string()));
+    }
+}
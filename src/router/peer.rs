@@ -3,11 +3,62 @@ use std::cmp::Ordering;
 use std::collections::BinaryHeap;
 use std::net::SocketAddr;
 
+/// cost of one jump, same unit as `Metric::link`
+pub const HOP_COST: u32 = 10;
+
 #[derive(Eq, Debug, Clone, PartialEq)]
 pub enum Host {
     Localhost,
     Unreachable,
     Socket(SocketAddr),
+    /// through the relay at the address with the name, the peer is not
+    /// reachable directly
+    Relay(SocketAddr, String),
+}
+
+impl Host {
+    /// the real address to send to, direct or the relay
+    pub fn socket(&self) -> Option<SocketAddr> {
+        match self {
+            Host::Socket(addr) | Host::Relay(addr, _) => Some(*addr),
+            Host::Localhost | Host::Unreachable => None,
+        }
+    }
+}
+
+/// the cost to reach a peer through one address, lower is better
+#[derive(Eq, Debug, Clone, Copy, PartialEq)]
+pub struct Metric {
+    /// metric the next hop announced, 0 if it is the node itself
+    pub path: u32,
+    /// link cost measured by keepalive, in milliseconds of RTT weighted by loss
+    pub link: u32,
+    /// jumps to the next hop
+    pub hops: u32,
+}
+
+impl Metric {
+    pub fn new(link: u32, hops: u32) -> Self {
+        Self::routed(0, link, hops)
+    }
+
+    /// metric of a route learned from a neighbor announced `path`
+    pub fn routed(path: u32, link: u32, hops: u32) -> Self {
+        Self { path, link, hops }
+    }
+
+    pub fn total(&self) -> u32 {
+        self.path
+            .saturating_add(self.link)
+            .saturating_add(self.hops.saturating_mul(HOP_COST))
+    }
+}
+
+impl Default for Metric {
+    /// cost of a address not measured yet, treat it as 100ms away
+    fn default() -> Self {
+        Self::new(100, 0)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -29,14 +80,23 @@ impl Peer {
             match t {
                 PeerInternal::Localhost => return Host::Localhost,
                 PeerInternal::Unreachable => return Host::Unreachable,
-                PeerInternal::Socket(addr, _) => return Host::Socket(*addr),
+                PeerInternal::Socket(addr, _, true, None) => return Host::Socket(*addr),
+                PeerInternal::Socket(addr, _, true, Some(relay)) => {
+                    return Host::Relay(*addr, relay.clone())
+                }
+                PeerInternal::Socket(_, _, false, _) => return Host::Unreachable,
             }
         };
         Host::Unreachable
     }
 
     pub fn add_host(&mut self, host: Host) -> Result<(), ()> {
-        info!("add host {:?} to {}", host, self.name);
+        self.add_host_with_metric(host, Metric::default())
+    }
+
+    /// add `host`, a known socket only get its metric updated and return `Err`
+    pub fn add_host_with_metric(&mut self, host: Host, metric: Metric) -> Result<(), ()> {
+        info!("add host {:?} {:?} to {}", host, metric, self.name);
         match host {
             Host::Localhost => {
                 for i in self.host.iter() {
@@ -54,28 +114,148 @@ impl Peer {
                 }
                 self.host.push(PeerInternal::Unreachable);
             }
-            Host::Socket(addr) => {
-                for i in self.host.iter() {
-                    if let PeerInternal::Socket(a, mut rank) = i {
-                        if *a == addr {
-                            use std::ops::AddAssign;
-                            rank.add_assign(1);
-                            return Err(());
-                        }
-                    }
+            Host::Socket(addr) => self.add_socket(addr, metric, None)?,
+            Host::Relay(addr, relay) => self.add_socket(addr, metric, Some(relay))?,
+        }
+        Ok(())
+    }
+
+    fn add_socket(
+        &mut self,
+        addr: SocketAddr,
+        metric: Metric,
+        relay: Option<String>,
+    ) -> Result<(), ()> {
+        if !self.sockets().contains(&addr) {
+            self.host
+                .push(PeerInternal::Socket(addr, metric, true, relay));
+            return Ok(());
+        }
+        // same address may become direct or relayed
+        let mut v = std::mem::take(&mut self.host).into_vec();
+        for i in v.iter_mut() {
+            if let PeerInternal::Socket(a, m, _, r) = i {
+                if *a == addr {
+                    *m = metric;
+                    *r = relay.clone();
                 }
-                self.host.push(PeerInternal::Socket(addr, 1));
             }
         }
+        self.host = BinaryHeap::from(v);
+        Err(())
+    }
+
+    /// remove `host`, return `Err` if the host is not in this peer
+    pub fn remove_host(&mut self, host: &Host) -> Result<(), ()> {
+        info!("remove host {:?} from {}", host, self.name);
+        let before = self.host.len();
+        self.host = self
+            .host
+            .drain()
+            .filter(|i| match (i, host) {
+                (PeerInternal::Localhost, Host::Localhost) => false,
+                (PeerInternal::Unreachable, Host::Unreachable) => false,
+                (PeerInternal::Socket(a, _, _, _), h) if h.socket().is_some() => {
+                    Some(*a) != h.socket()
+                }
+                _ => true,
+            })
+            .collect();
+        if self.host.len() == before {
+            return Err(());
+        }
         Ok(())
     }
+
+    /// no host left, the peer can be removed from the table
+    pub fn is_empty(&self) -> bool {
+        self.host.is_empty()
+    }
+
+    /// mark `addr` as reachable or not, return true if anything changed
+    ///
+    /// a unreachable address is kept, but any reachable one is preferred
+    pub fn set_reachable(&mut self, addr: SocketAddr, reachable: bool) -> bool {
+        self.update(addr, |_, r| *r = reachable)
+    }
+
+    /// set the measured link cost of `addr`, return true if anything changed
+    pub fn set_link_cost(&mut self, addr: SocketAddr, link: u32) -> bool {
+        self.update(addr, |m, _| m.link = link)
+    }
+
+    /// the neighbor at `old` move to `new`, return true if `old` is a host
+    ///
+    /// the host keep its metric and become reachable, `old` is just removed
+    /// if `new` is already a host
+    pub fn replace_socket(&mut self, old: SocketAddr, new: SocketAddr) -> bool {
+        let sockets = self.sockets();
+        if !sockets.contains(&old) {
+            return false;
+        }
+        if sockets.contains(&new) {
+            return self.remove_host(&Host::Socket(old)).is_ok();
+        }
+        let mut v = std::mem::take(&mut self.host).into_vec();
+        for i in v.iter_mut() {
+            if let PeerInternal::Socket(a, _, r, _) = i {
+                if *a == old {
+                    *a = new;
+                    *r = true;
+                }
+            }
+        }
+        self.host = BinaryHeap::from(v);
+        true
+    }
+
+    /// the metric of the best reachable address
+    pub fn get_metric(&self) -> Option<Metric> {
+        match self.host.peek() {
+            Some(PeerInternal::Socket(_, m, true, _)) => Some(*m),
+            Some(PeerInternal::Localhost) => Some(Metric::new(0, 0)),
+            _ => None,
+        }
+    }
+
+    /// modify the metric and reachable flag of `addr` and reorder the heap
+    fn update<F>(&mut self, addr: SocketAddr, f: F) -> bool
+    where
+        F: Fn(&mut Metric, &mut bool),
+    {
+        let mut changed = false;
+        let mut v = std::mem::take(&mut self.host).into_vec();
+        for i in v.iter_mut() {
+            if let PeerInternal::Socket(a, m, r, _) = i {
+                if *a == addr {
+                    let before = (*m, *r);
+                    f(m, r);
+                    changed |= before != (*m, *r);
+                }
+            }
+        }
+        self.host = BinaryHeap::from(v);
+        changed
+    }
+
+    /// all the real address of this peer, reachable or not
+    pub fn sockets(&self) -> Vec<SocketAddr> {
+        self.host
+            .iter()
+            .filter_map(|i| match i {
+                PeerInternal::Socket(a, _, _, _) => Some(*a),
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 #[derive(Eq, Debug, Clone)]
 enum PeerInternal {
     Localhost,
     Unreachable,
-    Socket(SocketAddr, u32),
+    /// address, metric, reachable, name of the relay if it is not the peer
+    Socket(SocketAddr, Metric, bool, Option<String>),
 }
 
 impl PartialEq for PeerInternal {
@@ -84,8 +264,10 @@ impl PartialEq for PeerInternal {
             return true;
         } else if let (PeerInternal::Unreachable, PeerInternal::Unreachable) = (other, self) {
             return true;
-        } else if let (PeerInternal::Socket(s1, r1), PeerInternal::Socket(s2, r2)) = (self, other) {
-            return s1 == s2 && r1 == r2;
+        } else if let (PeerInternal::Socket(s1, r1, u1, v1), PeerInternal::Socket(s2, r2, u2, v2)) =
+            (self, other)
+        {
+            return s1 == s2 && r1 == r2 && u1 == u2 && v1 == v2;
         }
         false
     }
@@ -111,14 +293,14 @@ impl Ord for PeerInternal {
             return Ordering::Less;
         } else if let PeerInternal::Unreachable = other {
             return Ordering::Greater;
-        } else if let (PeerInternal::Socket(_, r1), PeerInternal::Socket(_, r2)) = (other, self) {
-            if r1 > r2 {
-                return Ordering::Greater;
-            } else if r1 == r2 {
-                return Ordering::Equal;
-            } else {
-                return Ordering::Less;
+        } else if let (PeerInternal::Socket(_, r1, u1, _), PeerInternal::Socket(_, r2, u2, _)) =
+            (other, self)
+        {
+            if u1 != u2 {
+                return u2.cmp(u1);
             }
+            // lower metric is better, so it is greater in the heap
+            return r1.total().cmp(&r2.total());
         }
         unreachable!("no more ord")
     }
@@ -175,4 +357,114 @@ mod test {
             Host::Socket("128.66.1.0:1234".parse().unwrap())
         );
     }
+
+    #[test]
+    pub fn remove_host() {
+        let mut p = Peer::new("test".to_string());
+        let a = Host::Socket("128.66.1.0:1234".parse().unwrap());
+        let b = Host::Socket("128.66.1.1:1234".parse().unwrap());
+        assert!(p.add_host(a.clone()).is_ok());
+        assert!(p.add_host(b.clone()).is_ok());
+
+        assert!(p.remove_host(&a).is_ok());
+        assert!(p.remove_host(&a).is_err());
+        assert_eq!(p.get_host(), b);
+        assert!(!p.is_empty());
+
+        assert!(p.remove_host(&b).is_ok());
+        assert!(p.is_empty());
+        assert_eq!(p.get_host(), Host::Unreachable);
+    }
+
+    #[test]
+    pub fn fail_over() {
+        let mut p = Peer::new("test".to_string());
+        let a: SocketAddr = "128.66.1.0:1234".parse().unwrap();
+        let b: SocketAddr = "128.66.1.1:1234".parse().unwrap();
+        assert!(p.add_host(Host::Socket(a)).is_ok());
+        assert!(p.add_host(Host::Socket(b)).is_ok());
+        assert_eq!(p.get_host(), Host::Socket(a));
+
+        assert!(p.set_reachable(a, false));
+        assert!(!p.set_reachable(a, false));
+        assert_eq!(p.get_host(), Host::Socket(b));
+
+        assert!(p.set_reachable(b, false));
+        assert_eq!(p.get_host(), Host::Unreachable);
+
+        assert!(p.set_reachable(a, true));
+        assert_eq!(p.get_host(), Host::Socket(a));
+        assert!(p.set_reachable(b, true));
+        assert!(p.set_reachable(a, false));
+        assert_eq!(p.get_host(), Host::Socket(b));
+        assert_eq!(p.sockets().len(), 2);
+    }
+
+    #[test]
+    pub fn lowest_metric_win() {
+        let mut p = Peer::new("test".to_string());
+        let a: SocketAddr = "128.66.1.0:1234".parse().unwrap();
+        let b: SocketAddr = "128.66.1.1:1234".parse().unwrap();
+        assert!(p
+            .add_host_with_metric(Host::Socket(a), Metric::new(50, 1))
+            .is_ok());
+        assert!(p
+            .add_host_with_metric(Host::Socket(b), Metric::new(20, 2))
+            .is_ok());
+        assert_eq!(p.get_host(), Host::Socket(b));
+        assert_eq!(p.get_metric(), Some(Metric::new(20, 2)));
+
+        assert!(p.set_link_cost(b, 80));
+        assert!(!p.set_link_cost(b, 80));
+        assert_eq!(p.get_host(), Host::Socket(a));
+
+        assert!(p
+            .add_host_with_metric(Host::Socket(b), Metric::new(10, 0))
+            .is_err());
+        assert_eq!(p.get_host(), Host::Socket(b));
+    }
+
+    #[test]
+    pub fn roaming() {
+        let mut p = Peer::new("test".to_string());
+        let a: SocketAddr = "128.66.1.0:1234".parse().unwrap();
+        let b: SocketAddr = "128.66.1.1:1234".parse().unwrap();
+        let c: SocketAddr = "128.66.1.2:1234".parse().unwrap();
+        assert!(p
+            .add_host_with_metric(Host::Socket(a), Metric::new(10, 1))
+            .is_ok());
+        assert!(p
+            .add_host_with_metric(Host::Socket(b), Metric::new(50, 1))
+            .is_ok());
+        assert!(p.set_reachable(a, false));
+
+        assert!(p.replace_socket(a, c));
+        assert!(!p.replace_socket(a, c));
+        assert_eq!(p.get_host(), Host::Socket(c));
+        assert_eq!(p.get_metric(), Some(Metric::new(10, 1)));
+
+        // move to a address already known
+        assert!(p.replace_socket(c, b));
+        assert_eq!(p.sockets(), vec![b]);
+    }
+
+    #[test]
+    pub fn via_relay() {
+        let mut p = Peer::new("test".to_string());
+        let a: SocketAddr = "128.66.1.0:1234".parse().unwrap();
+        let relay = Host::Relay(a, "relay".to_string());
+        assert!(p
+            .add_host_with_metric(relay.clone(), Metric::new(20, 2))
+            .is_ok());
+        assert_eq!(p.get_host(), relay);
+        assert_eq!(p.get_host().socket(), Some(a));
+
+        // the relay become the peer itself
+        assert!(p
+            .add_host_with_metric(Host::Socket(a), Metric::new(20, 1))
+            .is_err());
+        assert_eq!(p.get_host(), Host::Socket(a));
+        assert!(p.remove_host(&relay).is_ok());
+        assert!(p.is_empty());
+    }
 }
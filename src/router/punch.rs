@@ -0,0 +1,102 @@
+use log::*;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// UDP hole punching state
+///
+/// a node only reachable through a relay is introduced by the relay, which
+/// tell both side the address it see the other one at, then both side send a
+/// hello to each other at the same time, so each NAT see a outgoing datagram
+/// before the incoming one and let it in
+#[derive(Debug)]
+pub struct Punch {
+    /// last time a introduction of the node is requested
+    requested: HashMap<String, Instant>,
+    /// endpoint of the node to punch, and when the introduction is received
+    punching: HashMap<String, (SocketAddr, Instant)>,
+    /// a node is requested at most once in it
+    retry: Duration,
+    /// punching is given up after it
+    timeout: Duration,
+}
+
+impl Punch {
+    pub fn new(retry: Duration, timeout: Duration) -> Self {
+        Self {
+            requested: HashMap::new(),
+            punching: HashMap::new(),
+            retry,
+            timeout,
+        }
+    }
+
+    /// true if a introduction of `name` should be requested now
+    pub fn should_request(&mut self, name: &str) -> bool {
+        if self.punching.contains_key(name) {
+            return false;
+        }
+        let now = Instant::now();
+        match self.requested.get(name) {
+            Some(t) if now.duration_since(*t) < self.retry => false,
+            _ => {
+                self.requested.insert(name.to_string(), now);
+                true
+            }
+        }
+    }
+
+    /// the relay tell `name` is at `addr`
+    pub fn start(&mut self, name: String, addr: SocketAddr) {
+        info!("punch {} at {}", name, addr);
+        self.punching.insert(name, (addr, Instant::now()));
+    }
+
+    /// `name` is connected directly
+    pub fn done(&mut self, name: &str) {
+        if self.punching.remove(name).is_some() {
+            info!("punch {} done", name);
+        }
+        self.requested.remove(name);
+    }
+
+    /// the endpoint to send a hello again, a expired one is given up and
+    /// the node stay behind the relay
+    pub fn tick(&mut self) -> Vec<SocketAddr> {
+        let timeout = self.timeout;
+        self.punching.retain(|name, (addr, started)| {
+            if started.elapsed() < timeout {
+                return true;
+            }
+            info!("give up punching {} at {}, keep the relay", name, addr);
+            false
+        });
+        self.punching.values().map(|(addr, _)| *addr).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn request_and_punch() {
+        let mut p = Punch::new(Duration::from_secs(60), Duration::from_secs(30));
+        let addr = "192.0.2.1:7654".parse().unwrap();
+
+        assert!(p.should_request("a"));
+        assert!(!p.should_request("a"));
+        assert!(p.should_request("b"));
+
+        p.start("a".to_string(), addr);
+        assert_eq!(p.tick(), vec![addr]);
+        p.done("a");
+        assert!(p.tick().is_empty());
+        assert!(p.should_request("a"));
+
+        // give up after the timeout
+        let mut p = Punch::new(Duration::from_secs(60), Duration::from_secs(0));
+        p.start("a".to_string(), addr);
+        assert!(p.tick().is_empty());
+    }
+}
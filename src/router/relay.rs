@@ -0,0 +1,58 @@
+use std::time::Instant;
+
+/// token bucket of the bytes a relay forward for other node,
+/// burst of one second
+#[derive(Debug)]
+pub struct Bandwidth {
+    /// bytes per second, 0 is unlimited
+    rate: u64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl Bandwidth {
+    pub fn new(rate: u64) -> Self {
+        Self {
+            rate,
+            tokens: rate as f64,
+            last: Instant::now(),
+        }
+    }
+
+    /// take `bytes` from the bucket, false if not enough
+    pub fn allow(&mut self, bytes: usize) -> bool {
+        if self.rate == 0 {
+            return true;
+        }
+        let now = Instant::now();
+        let d = now.duration_since(self.last);
+        let elapsed = d.as_secs() as f64 + f64::from(d.subsec_micros()) / 1_000_000.0;
+        self.tokens = (self.tokens + elapsed * self.rate as f64).min(self.rate as f64);
+        self.last = now;
+
+        if self.tokens < bytes as f64 {
+            return false;
+        }
+        self.tokens -= bytes as f64;
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn bandwidth() {
+        let mut b = Bandwidth::new(3000);
+        assert!(b.allow(1500));
+        assert!(b.allow(1400));
+        assert!(!b.allow(1500));
+        assert!(b.allow(50));
+
+        let mut unlimited = Bandwidth::new(0);
+        for _ in 0..100 {
+            assert!(unlimited.allow(65535));
+        }
+    }
+}
@@ -1,14 +1,18 @@
 use log::*;
-use radix_trie::Trie;
-use radix_trie::TrieCommon;
 use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
+use super::lpm::Lpm;
 use super::peer::Host;
+use super::peer::Metric;
 use super::peer::Peer;
 
 #[derive(Debug)]
 pub struct Table {
-    table: Trie<Vec<u8>, Peer>,
+    table: Lpm<Peer>,
+    /// count every change, a route cache can see it without the lock
+    generation: Arc<AtomicU64>,
 }
 
 pub trait LikeRouter {
@@ -30,7 +34,8 @@ pub enum Error {
 impl Default for Table {
     fn default() -> Self {
         Self {
-            table: Trie::default(),
+            table: Lpm::default(),
+            generation: Arc::new(AtomicU64::new(0)),
         }
     }
 }
@@ -39,15 +44,23 @@ impl Table {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// the counter is increased after every change of the table
+    pub fn generation(&self) -> Arc<AtomicU64> {
+        self.generation.clone()
+    }
+
+    fn changed(&self) {
+        self.generation.fetch_add(1, Ordering::Release);
+    }
 }
 
 impl Table {
     pub fn get_all_nodes(&self) -> Vec<SocketAddr> {
         let mut v = vec![];
         for (_, node) in self.table.iter() {
-            match node.get_host() {
-                Host::Localhost | Host::Unreachable => {}
-                Host::Socket(addr) => v.push(addr),
+            if let Some(addr) = node.get_host().socket() {
+                v.push(addr);
             }
         }
         v
@@ -62,6 +75,131 @@ impl Table {
         None
     }
 
+    /// the peer of exactly `addr/mask`, not the longest match
+    pub fn get(&self, addr: IpAddr, mask: u16) -> Option<&Peer> {
+        let (key, len) = prefix(addr, mask)?;
+        self.table.get(key, len)
+    }
+
+    /// remove `host` from the peer of `addr/mask`,
+    /// the whole entry is deleted if no host left
+    pub fn remove_host(&mut self, addr: IpAddr, mask: u16, host: &Host) -> Result<(), ()> {
+        let (key, len) = prefix(addr, mask).ok_or(())?;
+
+        let empty = match self.table.get_mut(key, len) {
+            None => return Err(()),
+            Some(p) => {
+                p.remove_host(host)?;
+                p.is_empty()
+            }
+        };
+        if empty {
+            self.delete(addr, mask)
+        } else {
+            self.changed();
+            Ok(())
+        }
+    }
+
+    /// every real address in the table, may have duplicate
+    pub fn get_all_sockets(&self) -> Vec<SocketAddr> {
+        let mut v = vec![];
+        for (_, node) in self.table.iter() {
+            v.extend(node.sockets());
+        }
+        v
+    }
+
+    /// mark `addr` reachable or not in every peer, return true if anything changed
+    pub fn set_reachable(&mut self, addr: SocketAddr, reachable: bool) -> bool {
+        let mut changed = false;
+        for (_, p) in self.table.iter_mut() {
+            changed |= p.set_reachable(addr, reachable);
+        }
+        if changed {
+            self.changed();
+        }
+        changed
+    }
+
+    /// set the measured link cost of `addr` in every peer,
+    /// return true if anything changed
+    pub fn set_link_cost(&mut self, addr: SocketAddr, link: u32) -> bool {
+        let mut changed = false;
+        for (_, p) in self.table.iter_mut() {
+            changed |= p.set_link_cost(addr, link);
+        }
+        if changed {
+            self.changed();
+        }
+        changed
+    }
+
+    /// same as `LikeRouter::insert` with the metric of the host
+    pub fn insert_with_metric(
+        &mut self,
+        addr: IpAddr,
+        mask: u16,
+        peer_name: String,
+        peer_host: Host,
+        metric: Metric,
+    ) -> Result<(), ()> {
+        let (key, len) = prefix(addr, mask).ok_or(())?;
+
+        let result = match self.table.get_mut(key, len) {
+            None => {
+                let mut peer = Peer::new(peer_name);
+                peer.add_host_with_metric(peer_host, metric).unwrap();
+                self.table.insert(key, len, peer);
+                Ok(())
+            }
+            Some(p) => p.add_host_with_metric(peer_host, metric),
+        };
+        // a known host get its metric updated and return `Err`, the best
+        // one may still be changed
+        self.changed();
+        result
+    }
+
+    /// the neighbor at `old` move to `new` in every peer,
+    /// return how many peer is touched
+    pub fn replace_socket(&mut self, old: SocketAddr, new: SocketAddr) -> usize {
+        let mut n = 0;
+        for (_, p) in self.table.iter_mut() {
+            if p.replace_socket(old, new) {
+                n += 1;
+            }
+        }
+        if n > 0 {
+            self.changed();
+        }
+        n
+    }
+
+    /// remove `addr` from every peer, delete the peer if no host left,
+    /// return how many peer is touched
+    pub fn remove_socket(&mut self, addr: SocketAddr) -> usize {
+        let host = Host::Socket(addr);
+        let mut n = 0;
+        let mut empty = vec![];
+        for (k, p) in self.table.iter_mut() {
+            if p.remove_host(&host).is_err() {
+                continue;
+            }
+            n += 1;
+            if p.is_empty() {
+                empty.push(k);
+            }
+        }
+        for (key, len) in empty {
+            self.table.remove(key, len);
+        }
+        if n > 0 {
+            self.changed();
+        }
+        n
+    }
+
     pub fn get_all_peer(&self) -> Vec<Peer> {
         let mut v = vec![];
         for (_, node) in self.table.iter() {
@@ -74,8 +212,7 @@ impl Table {
 
 impl LikeRouter for Table {
     fn find(&self, addr: IpAddr) -> Option<&Peer> {
-        let addr = encode_bytes(addr);
-        self.table.get_ancestor_value(&addr)
+        self.table.longest_match(encode(addr))
     }
 
     fn insert(
@@ -85,58 +222,37 @@ impl LikeRouter for Table {
         peer_name: String,
         peer_host: Host,
     ) -> Result<(), ()> {
-        let mut addr = encode_bytes(addr);
-        unsafe { addr.set_len(mask.into()) };
-
-        match self.table.get_mut(&addr) {
-            None => {
-                let mut peer = Peer::new(peer_name);
-                peer.add_host(peer_host).unwrap();
-                self.table.insert(addr, peer);
-                Ok(())
-            }
-            Some(p) => p.add_host(peer_host),
-        }
+        self.insert_with_metric(addr, mask, peer_name, peer_host, Metric::default())
     }
 
     fn delete(&mut self, addr: IpAddr, mask: u16) -> Result<(), ()> {
-        let mut addr = encode_bytes(addr);
-        unsafe { addr.set_len(mask.into()) };
-        match self.table.remove(&addr) {
+        let (key, len) = prefix(addr, mask).ok_or(())?;
+        match self.table.remove(key, len) {
             None => Err(()),
-            Some(_) => Ok(()),
+            Some(_) => {
+                self.changed();
+                Ok(())
+            }
         }
     }
 }
 
-fn encode_bytes(ip: IpAddr) -> Vec<u8> {
+/// the address as a key of `Lpm`, IPv4 take the high 32 bits like the
+/// prefix of a IPv6 address
+fn encode(ip: IpAddr) -> u128 {
     match ip {
-        IpAddr::V4(ip) => {
-            let mut v = Vec::with_capacity(4 * 8);
-            for b in ip.octets().iter() {
-                split_u8(*b, &mut v);
-            }
-            v
-        }
-        IpAddr::V6(ip) => {
-            let mut v = Vec::with_capacity(16 * 8);
-            for b in ip.octets().iter() {
-                split_u8(*b, &mut v);
-            }
-            v
-        }
+        IpAddr::V4(ip) => u128::from(u32::from(ip)) << 96,
+        IpAddr::V6(ip) => u128::from(ip),
     }
 }
 
-fn split_u8(u: u8, v: &mut Vec<u8>) {
-    v.push((u & 0b1000_0000) >> 7);
-    v.push((u & 0b0100_0000) >> 6);
-    v.push((u & 0b0010_0000) >> 5);
-    v.push((u & 0b0001_0000) >> 4);
-    v.push((u & 0b0000_1000) >> 3);
-    v.push((u & 0b0000_0100) >> 2);
-    v.push((u & 0b0000_0010) >> 1);
-    v.push(u & 0b0000_0001);
+/// the key and length of `addr/mask`, `None` if the mask is too long
+fn prefix(addr: IpAddr, mask: u16) -> Option<(u128, u8)> {
+    let width = if addr.is_ipv4() { 32 } else { 128 };
+    if mask > width {
+        return None;
+    }
+    Some((encode(addr), mask as u8))
 }
 
 #[cfg(test)]
@@ -218,6 +334,41 @@ mod test {
         assert!(ans3.is_none());
     }
 
+    #[test]
+    pub fn default_route() {
+        let mut table = Table::new();
+        let exit = Host::Socket("192.0.2.1:1234".parse().unwrap());
+        table
+            .insert("0.0.0.0".parse().unwrap(), 0, "exit".to_string(), exit)
+            .unwrap();
+        table
+            .insert(
+                "128.66.0.0".parse().unwrap(),
+                16,
+                "test1".to_string(),
+                Host::Localhost,
+            )
+            .unwrap();
+
+        // the longest match win over the default route
+        assert_eq!(
+            table.find("128.66.3.1".parse().unwrap()).unwrap().name,
+            "test1"
+        );
+        assert_eq!(table.find("1.1.1.1".parse().unwrap()).unwrap().name, "exit");
+        assert_eq!(
+            table.find("128.67.0.1".parse().unwrap()).unwrap().name,
+            "exit"
+        );
+
+        table.delete("0.0.0.0".parse().unwrap(), 0).unwrap();
+        assert!(table.find("1.1.1.1".parse().unwrap()).is_none());
+        assert_eq!(
+            table.find("128.66.3.1".parse().unwrap()).unwrap().name,
+            "test1"
+        );
+    }
+
     #[test]
     pub fn delete_from_table() {
         let mut table = Table::new();
@@ -288,4 +439,130 @@ mod test {
             .unwrap();
         assert_eq!(2, table.get_all_peer().len());
     }
+
+    /// compare `find` with a linear scan of the longest prefix, run with
+    /// `cargo test --release bench_find -- --ignored --nocapture`, add
+    /// `--features bench-radix` to compare with the bit-per-byte radix trie
+    /// the table replaced
+    #[test]
+    #[ignore]
+    pub fn bench_find() {
+        use std::net::{IpAddr, Ipv4Addr};
+        use std::time::Instant;
+
+        const ROUTES: usize = 10_000;
+        const LOOKUPS: usize = 1_000_000;
+        /// the scan is too slow for all of them
+        const SCANS: usize = 10_000;
+
+        fn scan(routes: &[(u32, u32)], addr: u32) -> Option<u32> {
+            routes
+                .iter()
+                .filter(|(net, mask)| addr & mask == *net)
+                .map(|(_, mask)| mask.count_ones())
+                .max()
+        }
+
+        let mut table = Table::new();
+        let mut routes = vec![(0, 0)];
+        table
+            .insert(
+                "0.0.0.0".parse().unwrap(),
+                0,
+                "default".to_string(),
+                Host::Localhost,
+            )
+            .unwrap();
+        for _ in 0..ROUTES {
+            let mask = 16 + rand::random::<u32>() % 9;
+            let addr = rand::random::<u32>();
+            // the random prefix may be taken already
+            if table
+                .insert(
+                    IpAddr::V4(Ipv4Addr::from(addr)),
+                    mask as u16,
+                    "peer".to_string(),
+                    Host::Localhost,
+                )
+                .is_err()
+            {
+                continue;
+            }
+            let mask = !0u32 << (32 - mask);
+            routes.push((addr & mask, mask));
+        }
+        let addrs: Vec<u32> = (0..LOOKUPS).map(|_| rand::random()).collect();
+
+        let start = Instant::now();
+        let found = addrs
+            .iter()
+            .filter(|a| table.find(Ipv4Addr::from(**a).into()).is_some())
+            .count();
+        let lpm = start.elapsed() / LOOKUPS as u32;
+        assert_eq!(found, LOOKUPS);
+
+        let start = Instant::now();
+        let best: Vec<u32> = addrs[..SCANS]
+            .iter()
+            .map(|a| scan(&routes, *a).unwrap())
+            .collect();
+        let linear = start.elapsed() / SCANS as u32;
+
+        // the same answer as the scan
+        for (a, best) in addrs.iter().zip(best) {
+            let net = IpAddr::V4(Ipv4Addr::from(*a));
+            let found = table.find(net).unwrap();
+            assert!(std::ptr::eq(found, table.get(net, best as u16).unwrap()));
+        }
+        println!(
+            "lookup in {} routes, lpm {:?}, linear scan {:?}",
+            ROUTES, lpm, linear
+        );
+
+        #[cfg(feature = "bench-radix")]
+        {
+            use radix_trie::Trie;
+
+            fn bits(addr: u32) -> Vec<u8> {
+                (0..32).rev().map(|i| ((addr >> i) & 1) as u8).collect()
+            }
+
+            let mut trie = Trie::new();
+            for (net, mask) in &routes {
+                let mut key = bits(*net);
+                key.truncate(mask.count_ones() as usize);
+                trie.insert(key, ());
+            }
+            let start = Instant::now();
+            let found_trie = addrs
+                .iter()
+                .filter(|a| trie.get_ancestor_value(&bits(**a)).is_some())
+                .count();
+            let radix = start.elapsed() / LOOKUPS as u32;
+            assert_eq!(found_trie, LOOKUPS);
+            println!("radix trie {:?}", radix);
+        }
+    }
+
+    #[test]
+    pub fn remove_host() {
+        let mut table = Table::new();
+        let a = Host::Socket("192.0.2.1:1234".parse().unwrap());
+        let b = Host::Socket("192.0.2.2:1234".parse().unwrap());
+        let net = "128.66.1.0".parse().unwrap();
+        table
+            .insert(net, 24, "test1".to_string(), a.clone())
+            .unwrap();
+        table
+            .insert(net, 24, "test1".to_string(), b.clone())
+            .unwrap();
+
+        table.remove_host(net, 24, &a).unwrap();
+        assert!(table.remove_host(net, 24, &a).is_err());
+        let ans = table.find("128.66.1.1".parse().unwrap()).unwrap();
+        assert_eq!(ans.get_host(), b);
+
+        table.remove_host(net, 24, &b).unwrap();
+        assert!(table.find("128.66.1.1".parse().unwrap()).is_none());
+    }
 }
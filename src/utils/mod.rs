@@ -1,5 +1,6 @@
 use log::*;
 use std::collections::HashMap;
+use std::net::IpAddr;
 use std::process::Command;
 
 const HEX_CHARS: &[u8] = b"0123456789ABCDEF";
@@ -14,6 +15,39 @@ pub fn bytes_to_hex(bytes: &[u8]) -> String {
     unsafe { String::from_utf8_unchecked(v) }
 }
 
+/// parse "10.1.0.0/16" or "fd00::/8", return the address and the mask
+pub fn parse_cidr(s: &str) -> Option<(IpAddr, u32)> {
+    let mut t = s.trim().splitn(2, '/');
+    let addr: IpAddr = t.next()?.parse().ok()?;
+    let max = match addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    };
+    let mask = match t.next() {
+        Some(m) => m.parse().ok()?,
+        None => max,
+    };
+    if mask > max {
+        return None;
+    }
+    Some((addr, mask))
+}
+
+/// is `addr` inside `net/mask`
+pub fn cidr_contains(net: IpAddr, mask: u32, addr: IpAddr) -> bool {
+    match (net, addr) {
+        (IpAddr::V4(n), IpAddr::V4(a)) => {
+            let m = if mask == 0 { 0 } else { !0u32 << (32 - mask) };
+            u32::from(n) & m == u32::from(a) & m
+        }
+        (IpAddr::V6(n), IpAddr::V6(a)) => {
+            let m = if mask == 0 { 0 } else { !0u128 << (128 - mask) };
+            u128::from(n) & m == u128::from(a) & m
+        }
+        _ => false,
+    }
+}
+
 pub struct Reader;
 
 impl Reader {